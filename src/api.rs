@@ -1,31 +1,117 @@
-use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use reqwest::{Client, Response};
+use futures::{stream, StreamExt};
+use reqwest::{
+    multipart::{Form, Part},
+    Client, Response,
+};
 use serde::{de::DeserializeOwned, Serialize};
 use tokio::{
+    process::Command,
     sync::{mpsc::Sender, Mutex},
     try_join,
 };
 
 use crate::{
-    app::{App, Notification, Route, SelectedCategory},
+    app::{
+        reconnection_backoff, sort_names, torrent_file_path, unix_now, App, CompletedTorrent,
+        Notification, RecheckStep, Route, RECENTLY_COMPLETED_MAX,
+    },
+    command::{build_command_args, open_path},
     model::{
-        Category, DeleteTorrentParams, GetMainDataParams, GetTorrentFilesParams,
-        GetTorrentListParams, Hashes, LoginPayload, MainData, SpeedLimitsMode, TorrentFile,
-        TorrentInfo, TransferInfo,
+        has_no_working_tracker, AddTorrentOptions, AddTorrentPayload, AddTrackersPayload, Category,
+        CategoryPayload, DeleteTorrentParams, FilePriorityPayload, ForceStartPayload,
+        GetMainDataParams, GetTorrentFilesParams, GetTorrentListParams, GetTorrentPeersParams,
+        GetTorrentPropertiesParams, GetTorrentTrackersParams, Hashes, LoginPayload, MainData,
+        Preferences, RemoveCategoriesPayload, SetCategoryPayload, SetLocationPayload,
+        SpeedLimitsMode, StateFilter, TagsListPayload, TagsPayload, TorrentFile, TorrentInfo,
+        TorrentPeers, TorrentProperties, TrackerInfo, TransferInfo, UploadLimitPayload,
     },
     ui::UiEvent,
 };
 
+const TRACKERS_SCAN_CONCURRENCY: usize = 8;
+
+// if checking was not seen by then, it is considered done
+const RECHECK_START_TIMEOUT: Duration = Duration::from_secs(5);
+
+// relogins further apart are not counted as being in a row
+const RELOGIN_RESET_AFTER: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Debug)]
 pub enum ApiEvent {
     Reload,
+    Reconnect,
     Sync,
     Files(String),
+    FileList(String), // never opens a file directly, unlike Files
+    Peers(String),
+    Properties(String),
+    ScanTrackers(Vec<String>),
+    AddTrackers(Vec<String>, Vec<String>), // hashes, tracker urls
+    ToggleSpeedLimits,
+    AddUrl(String, AddTorrentOptions),
+    AddTorrentFile(PathBuf, bool),       // path, paused, from the inbox
+    AddFile(PathBuf, AddTorrentOptions), // typed in the add prompt, the file is left as is
+    Preferences,
     Delete(String),
     DeleteFiles(String),
     Pause(String),
     Resume(String),
+    PauseMany(Vec<String>),
+    ResumeMany(Vec<String>),
+    ForceStartMany(Vec<String>),
+    Reannounce(String),
+    ReannounceMany(Vec<String>),
+    SetFilePriority(String, Vec<i32>, i32), // hash, file indexes, priority
+    SetLocation(String, String),            // hash, new save path, the data is moved
+    SetUploadLimit(String, i64),            // hash, bytes/s, 0 removes the limit
+    SetTags(String, Vec<String>, Vec<String>), // hash, tags to add, tags to remove
+    RunCommand(Vec<String>),
+    Recheck(String),
+    RecheckAndResume(String),
+    SetCategory(String, String),          // hash, category
+    CreateAndSetCategory(String, String), // hash, new category to set
+    CreateCategory(String, String),       // name, save path
+    EditCategory(String, String),         // name, save path
+    RemoveCategory(String),
+    CreateTags(Vec<String>),
+    DeleteTag(String),
+    Login, // with the credentials from the login prompt
+}
+
+impl ApiEvent {
+    /// Torrents changed by the event, shown as pending until it's handled
+    fn hashes(&self) -> Vec<String> {
+        match self {
+            Self::Delete(hash)
+            | Self::DeleteFiles(hash)
+            | Self::Pause(hash)
+            | Self::Resume(hash)
+            | Self::SetTags(hash, _, _)
+            | Self::Reannounce(hash)
+            | Self::Recheck(hash)
+            | Self::RecheckAndResume(hash)
+            | Self::SetCategory(hash, _)
+            | Self::CreateAndSetCategory(hash, _)
+            | Self::SetUploadLimit(hash, _)
+            | Self::SetFilePriority(hash, _, _)
+            | Self::SetLocation(hash, _) => vec![hash.clone()],
+            Self::PauseMany(hashes)
+            | Self::ResumeMany(hashes)
+            | Self::ForceStartMany(hashes)
+            | Self::ReannounceMany(hashes)
+            | Self::AddTrackers(hashes, _) => hashes.clone(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -34,6 +120,7 @@ pub struct Api {
     base_url: String,
     username: Option<String>,
     password: Option<String>,
+    is_logged_in: bool, // no session to close otherwise
 }
 
 #[derive(Debug)]
@@ -46,15 +133,70 @@ pub enum LoginError {
 pub enum ExternalError {
     Connection(reqwest::Error),
     Internal,
+    WrongUrl(String), // 404 outside of the endpoints for a single torrent
 }
 
 #[derive(Debug)]
 pub enum ApiError {
     External(ExternalError),
     NotAuthenticated,
+    NotFound,
     Login(LoginError),
 }
 
+impl fmt::Display for LoginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongCredentials => write!(f, "wrong credentials"),
+            Self::TooManyAttempts => write!(f, "too many failed login attempts"),
+        }
+    }
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connection(e) => write!(f, "connection error: {e}"),
+            Self::Internal => write!(f, "unexpected response from qBittorrent"),
+            Self::WrongUrl(url) => write!(f, "{url} is not found"),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::External(e) => e.fmt(f),
+            Self::NotAuthenticated => write!(f, "not authenticated"),
+            Self::NotFound => write!(f, "not found"),
+            Self::Login(e) => e.fmt(f),
+        }
+    }
+}
+
+impl ApiError {
+    /// Suggested fix, shown with the error
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::External(ExternalError::Connection(_)) => {
+                "Check that qBittorrent is running and its Web UI is reachable at the url"
+            }
+            Self::External(ExternalError::Internal) => "Check the qBittorrent log",
+            Self::External(ExternalError::WrongUrl(_)) => {
+                "Check that the url points to the qBittorrent Web UI"
+            }
+            Self::NotAuthenticated => {
+                "Pass --username and --password, or enable authentication bypass in the Web UI settings"
+            }
+            Self::NotFound => "The torrent was probably removed",
+            Self::Login(LoginError::WrongCredentials) => "Check --username and --password",
+            Self::Login(LoginError::TooManyAttempts) => {
+                "The IP is banned after failed logins, wait or unban it in the Web UI settings"
+            }
+        }
+    }
+}
+
 impl From<LoginError> for ApiError {
     fn from(value: LoginError) -> Self {
         Self::Login(value)
@@ -67,6 +209,23 @@ impl From<reqwest::Error> for ApiError {
     }
 }
 
+// endpoints that answer 404 for a hash of a removed torrent
+const HASH_SCOPED_PATHS: [&str; 4] = [
+    "/torrents/files",
+    "/torrents/trackers",
+    "/torrents/properties",
+    "/sync/torrentPeers",
+];
+
+// anywhere else a 404 means the url is not the Web UI
+fn not_found_error(path: &str, url: &reqwest::Url) -> ApiError {
+    if HASH_SCOPED_PATHS.contains(&path) {
+        ApiError::NotFound
+    } else {
+        ApiError::External(ExternalError::WrongUrl(url.to_string()))
+    }
+}
+
 impl Api {
     fn new(
         base_url: &str,
@@ -85,6 +244,7 @@ impl Api {
             base_url: base_url.to_owned(),
             username,
             password,
+            is_logged_in: false,
         }
     }
 
@@ -106,6 +266,9 @@ impl Api {
         if res.status() == 403 {
             return Err(ApiError::NotAuthenticated);
         }
+        if res.status() == 404 {
+            return Err(not_found_error(path, res.url()));
+        }
 
         Ok(res.text().await?)
     }
@@ -124,6 +287,9 @@ impl Api {
         if res.status() == 403 {
             return Err(ApiError::NotAuthenticated);
         }
+        if res.status() == 404 {
+            return Err(not_found_error(path, res.url()));
+        }
 
         Ok(res.json().await?)
     }
@@ -166,6 +332,15 @@ impl Api {
         Ok(res)
     }
 
+    pub fn set_credentials(&mut self, username: String, password: String) {
+        self.username = Some(username);
+        self.password = Some(password);
+    }
+
+    pub fn has_credentials(&self) -> bool {
+        self.username.is_some() && self.password.is_some()
+    }
+
     pub async fn login(&mut self) -> Result<(), ApiError> {
         // 200, Ok. - ok
         // 200, Fails. - wrong creds
@@ -179,7 +354,10 @@ impl Api {
         if res.status() == 200 {
             let body = res.text().await.unwrap();
             match body.as_str() {
-                "Ok." => Ok(()),
+                "Ok." => {
+                    self.is_logged_in = true;
+                    Ok(())
+                }
                 "Fails." => Err(LoginError::WrongCredentials.into()),
                 _ => unreachable!(),
             }
@@ -191,7 +369,11 @@ impl Api {
     }
 
     pub async fn logout(&mut self) -> Result<(), ApiError> {
+        if !self.is_logged_in {
+            return Ok(());
+        }
         tracing::debug!("Logout");
+        self.is_logged_in = false;
         self.post_with_timeout::<()>("/auth/logout", None, Duration::from_millis(500))
             .await?;
         Ok(())
@@ -208,7 +390,17 @@ impl Api {
         Ok(SpeedLimitsMode::from(text))
     }
 
-    async fn torrents_info(
+    async fn toggle_speed_limits_mode(&self) -> Result<(), ApiError> {
+        self.post::<()>("/transfer/toggleSpeedLimitsMode", None)
+            .await?;
+        Ok(())
+    }
+
+    async fn preferences(&self) -> Result<Preferences, ApiError> {
+        self.get_json::<_, ()>("/app/preferences", None).await
+    }
+
+    pub async fn torrents_info(
         &self,
         query: Option<GetTorrentListParams>,
     ) -> Result<Vec<TorrentInfo>, ApiError> {
@@ -222,6 +414,69 @@ impl Api {
         self.get_json("/torrents/files", Some(query)).await
     }
 
+    /// 200 even if the torrent was not added, false then
+    async fn add_torrent(&self, payload: AddTorrentPayload) -> Result<bool, ApiError> {
+        let text = self
+            .post("/torrents/add", Some(payload))
+            .await?
+            .text()
+            .await?;
+        Ok(text.trim() != "Fails.")
+    }
+
+    async fn add_torrent_file(
+        &self,
+        file_name: String,
+        data: Vec<u8>,
+        options: AddTorrentOptions,
+    ) -> Result<bool, ApiError> {
+        let part = Part::bytes(data)
+            .file_name(file_name)
+            .mime_str("application/x-bittorrent")?;
+        // same as paused, qBittorrent 5 renamed it
+        let mut form = Form::new()
+            .part("torrents", part)
+            .text("paused", options.paused.to_string())
+            .text("stopped", options.paused.to_string());
+        if let Some(category) = options.category {
+            form = form.text("category", category);
+        }
+        if let Some(save_path) = options.save_path {
+            form = form.text("savepath", save_path);
+        }
+        let res = self
+            .client
+            .post(self.build_url("/torrents/add"))
+            .multipart(form)
+            .send()
+            .await?;
+        if res.status() == 403 {
+            return Err(ApiError::NotAuthenticated);
+        }
+
+        let text = res.text().await?;
+        Ok(text.trim() != "Fails.")
+    }
+
+    async fn add_trackers(&self, payload: AddTrackersPayload) -> Result<(), ApiError> {
+        self.post("/torrents/addTrackers", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn torrent_trackers(
+        &self,
+        query: GetTorrentTrackersParams,
+    ) -> Result<Vec<TrackerInfo>, ApiError> {
+        self.get_json("/torrents/trackers", Some(query)).await
+    }
+
+    async fn torrent_properties(
+        &self,
+        query: GetTorrentPropertiesParams,
+    ) -> Result<TorrentProperties, ApiError> {
+        self.get_json("/torrents/properties", Some(query)).await
+    }
+
     async fn pause(&self, hashes: &[&str]) -> Result<(), ApiError> {
         let payload = Hashes::from(hashes);
         self.post("/torrents/pause", Some(payload)).await?;
@@ -234,10 +489,125 @@ impl Api {
         Ok(())
     }
 
+    pub async fn pause_all(&self) -> Result<(), ApiError> {
+        self.pause(&["all"]).await
+    }
+
+    pub async fn resume_all(&self) -> Result<(), ApiError> {
+        self.resume(&["all"]).await
+    }
+
+    async fn set_upload_limit(&self, hashes: &[&str], limit: i64) -> Result<(), ApiError> {
+        let payload = UploadLimitPayload {
+            hashes: hashes.join("|"),
+            limit,
+        };
+        self.post("/torrents/setUploadLimit", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn set_force_start(&self, hashes: &[&str], value: bool) -> Result<(), ApiError> {
+        let payload = ForceStartPayload::new(hashes, value);
+        self.post("/torrents/setForceStart", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn set_file_priority(
+        &self,
+        hash: &str,
+        ids: &[i32],
+        priority: i32,
+    ) -> Result<(), ApiError> {
+        let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+        let payload = FilePriorityPayload {
+            hash: hash.to_owned(),
+            id: ids.join("|"),
+            priority,
+        };
+        self.post("/torrents/filePrio", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn set_location(&self, hashes: &[&str], location: &str) -> Result<(), ApiError> {
+        let payload = SetLocationPayload {
+            hashes: hashes.join("|"),
+            location: location.to_owned(),
+        };
+        self.post("/torrents/setLocation", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn reannounce(&self, hashes: &[&str]) -> Result<(), ApiError> {
+        let payload = Hashes::from(hashes);
+        self.post("/torrents/reannounce", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn recheck(&self, hashes: &[&str]) -> Result<(), ApiError> {
+        let payload = Hashes::from(hashes);
+        self.post("/torrents/recheck", Some(payload)).await?;
+        Ok(())
+    }
+
     async fn categories(&self) -> Result<HashMap<String, Category>, ApiError> {
         self.get_json::<_, ()>("/torrents/categories", None).await
     }
 
+    async fn tags(&self) -> Result<Vec<String>, ApiError> {
+        self.get_json::<_, ()>("/torrents/tags", None).await
+    }
+
+    async fn add_tags(&self, payload: TagsPayload) -> Result<(), ApiError> {
+        self.post("/torrents/addTags", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn remove_tags(&self, payload: TagsPayload) -> Result<(), ApiError> {
+        self.post("/torrents/removeTags", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn set_category(&self, payload: SetCategoryPayload) -> Result<(), ApiError> {
+        self.post("/torrents/setCategory", Some(payload)).await?;
+        Ok(())
+    }
+
+    /// False if the name is taken or invalid, qBittorrent does not say which
+    async fn create_category(&self, payload: CategoryPayload) -> Result<bool, ApiError> {
+        let res = self.post("/torrents/createCategory", Some(payload)).await?;
+        Ok(res.status() != 409)
+    }
+
+    async fn edit_category(&self, payload: CategoryPayload) -> Result<(), ApiError> {
+        self.post("/torrents/editCategory", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn remove_categories(&self, categories: &[&str]) -> Result<(), ApiError> {
+        let payload = RemoveCategoriesPayload {
+            categories: categories.join("\n"),
+        };
+        self.post("/torrents/removeCategories", Some(payload))
+            .await?;
+        Ok(())
+    }
+
+    async fn create_tags(&self, tags: &[String]) -> Result<(), ApiError> {
+        let payload = TagsListPayload {
+            tags: tags.join(","),
+        };
+        self.post("/torrents/createTags", Some(payload)).await?;
+        Ok(())
+    }
+
+    async fn delete_tags(&self, tags: &[&str]) -> Result<(), ApiError> {
+        let payload = TagsListPayload {
+            tags: tags.join(","),
+        };
+        self.post("/torrents/deleteTags", Some(payload)).await?;
+        Ok(())
+    }
+
     async fn delete(&self, payload: DeleteTorrentParams) -> Result<(), ApiError> {
         self.post("/torrents/delete", Some(payload)).await?;
         Ok(())
@@ -246,6 +616,13 @@ impl Api {
     async fn sync_maindata(&self, query: GetMainDataParams) -> Result<MainData, ApiError> {
         self.get_json("/sync/maindata", Some(query)).await
     }
+
+    async fn sync_torrent_peers(
+        &self,
+        query: GetTorrentPeersParams,
+    ) -> Result<TorrentPeers, ApiError> {
+        self.get_json("/sync/torrentPeers", Some(query)).await
+    }
 }
 
 pub struct ApiHandler {
@@ -253,7 +630,12 @@ pub struct ApiHandler {
     ui_tx: Sender<UiEvent>,
     pub api: Api,
     rid: i64,
+    peers_hash: Option<String>,
+    peers_rid: i64,
     current_event: ApiEvent,
+    was_disconnected: bool, // until the first sync after reconnection
+    relogin_attempt_n: usize,
+    last_relogin_ts: Option<Instant>,
 }
 
 impl ApiHandler {
@@ -275,11 +657,39 @@ impl ApiHandler {
             ui_tx,
             app,
             rid: 0,
+            peers_hash: None,
+            peers_rid: 0,
             current_event: ApiEvent::Sync,
+            was_disconnected: false,
+            relogin_attempt_n: 0,
+            last_relogin_ts: None,
         }
     }
 
     pub async fn handle(&mut self, event: ApiEvent) -> Result<(), ApiError> {
+        let hashes = event.hashes();
+        if hashes.is_empty() {
+            return self.handle_event(event).await;
+        }
+
+        self.app
+            .lock()
+            .await
+            .pending_hashes
+            .extend(hashes.iter().cloned());
+        self.ui_tx.send(UiEvent::Redraw).await.unwrap();
+        let result = self.handle_event(event).await;
+        {
+            let mut app = self.app.lock().await;
+            for hash in hashes.iter() {
+                app.pending_hashes.remove(hash);
+            }
+        }
+        self.ui_tx.send(UiEvent::Redraw).await.unwrap();
+        result
+    }
+
+    async fn handle_event(&mut self, event: ApiEvent) -> Result<(), ApiError> {
         tracing::debug!(?event);
         self.current_event = event.clone();
         let input_event: Option<UiEvent> = match event {
@@ -287,8 +697,33 @@ impl ApiHandler {
                 self.reload().await?;
                 None
             }
+            ApiEvent::Login => {
+                let (username, password) = {
+                    let mut app = self.app.lock().await;
+                    let password = app.login_password.value().to_owned();
+                    app.login_password.clear();
+                    (app.login_username.value().to_owned(), password)
+                };
+                self.api.set_credentials(username, password);
+                self.api.login().await?;
+                self.app.lock().await.login_error = None;
+                self.sync().await?;
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::Reconnect => {
+                if self.api.has_credentials() {
+                    self.api.login().await?;
+                }
+                // the last rid tells if the server still has the state, see sync
+                self.sync().await?;
+                Some(UiEvent::Redraw)
+            }
             ApiEvent::Sync => {
                 self.sync().await?;
+                self.sync_peers().await?;
+                self.check_data_cap().await?;
+                self.advance_pending_rechecks().await?;
+                self.run_on_complete_actions().await;
                 let mut app = self.app.lock().await;
                 app.trace_handle_sync_event_n += 1;
                 None
@@ -301,19 +736,71 @@ impl ApiHandler {
                 self.api.resume(&[&hash]).await?;
                 Some(UiEvent::Tick)
             }
+            ApiEvent::PauseMany(hashes) => {
+                let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+                self.api.pause(&hashes).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::ResumeMany(hashes) => {
+                let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+                self.api.resume(&hashes).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::ForceStartMany(hashes) => {
+                let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+                self.api.set_force_start(&hashes, true).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::Reannounce(hash) => {
+                self.api.reannounce(&[&hash]).await?;
+                let mut app = self.app.lock().await;
+                app.notification = Some(Notification::Reannounced(1));
+                Some(UiEvent::Redraw)
+            }
+            // one request for all of them
+            ApiEvent::ReannounceMany(hashes) => {
+                let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+                self.api.reannounce(&hashes).await?;
+                let mut app = self.app.lock().await;
+                app.notification = Some(Notification::Reannounced(hashes.len()));
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::SetUploadLimit(hash, limit) => {
+                self.api.set_upload_limit(&[&hash], limit).await?;
+                let mut app = self.app.lock().await;
+                if let Some(torrent) = app.torrents.iter_mut().find(|t| t.hash == hash) {
+                    torrent.up_limit = limit;
+                }
+                app.notification = Some(Notification::UploadLimitSet(limit));
+                Some(UiEvent::Tick)
+            }
             ApiEvent::Files(hash) => {
                 let files = self.api.torrents_files(hash.clone().into()).await?;
 
                 let mut app = self.app.lock().await;
                 if let Some(ref torrent) = app.current_torrent {
                     if files.len() == 1 {
-                        let path = Path::new(&torrent.content_path);
-                        if path.exists() {
-                            open::that_in_background(path);
-                        } else {
+                        let path = app
+                            .config
+                            .rewrite_path(&torrent.content_path, &torrent.category);
+                        if !path.exists() {
+                            app.notification = Some(Notification::FileNotFound);
+                        } else if let Err(reason) = open_path(&app.config, &path) {
+                            app.notification = Some(Notification::CommandFailed(reason));
+                        }
+                        Some(UiEvent::Redraw)
+                    } else if let Some(file) = app
+                        .config
+                        .open_main_file
+                        .and_then(|main_file| main_file.pick(&files))
+                    {
+                        let path = torrent_file_path(&app.config, torrent, file);
+                        if !path.exists() {
                             app.notification = Some(Notification::FileNotFound);
+                        } else if let Err(reason) = open_path(&app.config, &path) {
+                            app.notification = Some(Notification::CommandFailed(reason));
                         }
-                        None
+                        Some(UiEvent::Redraw)
                     } else {
                         app.current_torrent_files = Some(files);
                         app.files_list.state.select(Some(0));
@@ -324,6 +811,203 @@ impl ApiHandler {
                     None
                 }
             }
+            ApiEvent::FileList(hash) => {
+                let files = self.api.torrents_files(hash.into()).await?;
+
+                let mut app = self.app.lock().await;
+                app.current_torrent_files = Some(files);
+                app.files_list.state.select(Some(0));
+                app.current_route = Route::Files;
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::SetLocation(hash, location) => {
+                self.api.set_location(&[&hash], &location).await?;
+                Some(UiEvent::Tick)
+            }
+            // one request for all files of a directory
+            ApiEvent::SetFilePriority(hash, ids, priority) => {
+                self.api.set_file_priority(&hash, &ids, priority).await?;
+                let mut app = self.app.lock().await;
+                let is_current = app
+                    .current_torrent
+                    .as_ref()
+                    .is_some_and(|torrent| torrent.hash == hash);
+                if let (true, Some(files)) = (is_current, app.current_torrent_files.as_mut()) {
+                    for file in files.iter_mut().filter(|f| ids.contains(&f.index)) {
+                        file.priority = priority;
+                    }
+                }
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::Peers(hash) => {
+                // new torrent or reopened view, start from a full update
+                self.peers_hash = Some(hash);
+                self.peers_rid = 0;
+                self.sync_peers().await?;
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::Properties(hash) => {
+                let (properties, trackers) = try_join!(
+                    self.api.torrent_properties(hash.clone().into()),
+                    self.api.torrent_trackers(hash.clone().into()),
+                )?;
+
+                let mut app = self.app.lock().await;
+                // the info page could be left or switched to another torrent meanwhile
+                match app.current_torrent {
+                    Some(ref torrent) if torrent.hash == hash => {
+                        app.current_torrent_properties = Some(properties);
+                        app.current_torrent_trackers = Some(trackers);
+                        Some(UiEvent::Redraw)
+                    }
+                    _ => None,
+                }
+            }
+            ApiEvent::ScanTrackers(hashes) => {
+                // one request per torrent, a few at a time
+                let api = &self.api;
+                let results: Vec<_> = stream::iter(hashes)
+                    .map(|hash| async move {
+                        let trackers = api.torrent_trackers(hash.clone().into()).await;
+                        (hash, trackers)
+                    })
+                    .buffer_unordered(TRACKERS_SCAN_CONCURRENCY)
+                    .collect()
+                    .await;
+
+                let mut failing = HashSet::new();
+                for (hash, trackers) in results {
+                    match trackers {
+                        Ok(trackers) if has_no_working_tracker(&trackers) => {
+                            failing.insert(hash);
+                        }
+                        Ok(_) | Err(ApiError::NotFound) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                let mut app = self.app.lock().await;
+                app.no_working_tracker = Some(failing);
+                if app.only_no_working_tracker {
+                    app.select_first_torrent();
+                }
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::AddTrackers(hashes, urls) => {
+                let api = &self.api;
+                let urls = urls.join("\n");
+                let results: Vec<_> = stream::iter(hashes)
+                    .map(|hash| {
+                        let payload = AddTrackersPayload {
+                            hash,
+                            urls: urls.clone(),
+                        };
+                        async move { api.add_trackers(payload).await }
+                    })
+                    .buffer_unordered(TRACKERS_SCAN_CONCURRENCY)
+                    .collect()
+                    .await;
+
+                let mut updated_n = 0;
+                for result in results {
+                    match result {
+                        Ok(()) => updated_n += 1,
+                        Err(ApiError::NotFound) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                let mut app = self.app.lock().await;
+                app.notification = Some(Notification::AddedTrackers(updated_n));
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::AddTorrentFile(path, paused) => match tokio::fs::read(&path).await {
+                Ok(data) => {
+                    let file_name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let options = AddTorrentOptions {
+                        paused,
+                        ..Default::default()
+                    };
+                    let is_added = self.api.add_torrent_file(file_name, data, options).await?;
+                    let mut app = self.app.lock().await;
+                    if !is_added {
+                        app.notification = Some(Notification::AddTorrentFailed);
+                    } else if let Err(e) = app.config.inbox_after_add.apply(&path) {
+                        app.notification = Some(Notification::InboxFailed(format!(
+                            "{}: {e}",
+                            path.display()
+                        )));
+                    }
+                    Some(UiEvent::Redraw)
+                }
+                Err(e) => {
+                    let mut app = self.app.lock().await;
+                    app.notification = Some(Notification::InboxFailed(format!(
+                        "{}: {e}",
+                        path.display()
+                    )));
+                    Some(UiEvent::Redraw)
+                }
+            },
+            ApiEvent::AddFile(path, options) => match tokio::fs::read(&path).await {
+                Ok(data) => {
+                    let file_name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    if self.api.add_torrent_file(file_name, data, options).await? {
+                        Some(UiEvent::Tick)
+                    } else {
+                        let mut app = self.app.lock().await;
+                        app.notification = Some(Notification::AddTorrentFailed);
+                        Some(UiEvent::Redraw)
+                    }
+                }
+                Err(e) => {
+                    let mut app = self.app.lock().await;
+                    app.notification = Some(Notification::ReadTorrentFileFailed(format!(
+                        "{}: {e}",
+                        path.display()
+                    )));
+                    Some(UiEvent::Redraw)
+                }
+            },
+            ApiEvent::AddUrl(url, options) => {
+                if self
+                    .api
+                    .add_torrent(AddTorrentPayload::new(url.clone(), options))
+                    .await?
+                {
+                    let mut app = self.app.lock().await;
+                    app.add_history.push(&url);
+                    // best effort, the history is only a safety net
+                    let _ = app.add_history.save();
+                    Some(UiEvent::Tick)
+                } else {
+                    let mut app = self.app.lock().await;
+                    app.notification = Some(Notification::AddTorrentFailed);
+                    Some(UiEvent::Redraw)
+                }
+            }
+            ApiEvent::ToggleSpeedLimits => {
+                self.api.toggle_speed_limits_mode().await?;
+                let mut app = self.app.lock().await;
+                app.transfer_info.use_alt_speed_limits = !app.transfer_info.use_alt_speed_limits;
+                Some(UiEvent::Redraw)
+            }
+            ApiEvent::Preferences => {
+                // fetched on every visit, the server settings can be changed elsewhere
+                let preferences = self.api.preferences().await?;
+
+                let mut app = self.app.lock().await;
+                app.preferences = preferences;
+                app.preferences_state.scroll = 0;
+                app.current_route = Route::Preferences;
+                Some(UiEvent::Redraw)
+            }
             ApiEvent::Delete(hash) => {
                 self.api
                     .delete(DeleteTorrentParams {
@@ -342,10 +1026,107 @@ impl ApiHandler {
                     .await?;
                 Some(UiEvent::Tick)
             }
+            ApiEvent::SetTags(hash, tags_to_add, tags_to_remove) => {
+                if !tags_to_add.is_empty() {
+                    self.api
+                        .add_tags(TagsPayload::new(&hash, &tags_to_add))
+                        .await?;
+                }
+                if !tags_to_remove.is_empty() {
+                    self.api
+                        .remove_tags(TagsPayload::new(&hash, &tags_to_remove))
+                        .await?;
+                }
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::RunCommand(args) => self.run_command(args).await,
+            ApiEvent::SetCategory(hash, category) => {
+                self.api
+                    .set_category(SetCategoryPayload {
+                        hashes: hash,
+                        category,
+                    })
+                    .await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::CreateAndSetCategory(hash, category) => {
+                // an existing category can be set all the same
+                self.api
+                    .create_category(CategoryPayload {
+                        category: category.clone(),
+                        save_path: String::new(),
+                    })
+                    .await?;
+                self.api
+                    .set_category(SetCategoryPayload {
+                        hashes: hash,
+                        category,
+                    })
+                    .await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::CreateCategory(category, save_path) => {
+                let is_created = self
+                    .api
+                    .create_category(CategoryPayload {
+                        category: category.clone(),
+                        save_path: save_path.clone(),
+                    })
+                    .await?;
+                let mut app = self.app.lock().await;
+                if is_created {
+                    // added right away, not on the next sync, to be highlighted
+                    app.category_save_paths.insert(category.clone(), save_path);
+                    let mut categories = app.categories.clone();
+                    if !categories.contains(&category) {
+                        categories.push(category.clone());
+                    }
+                    app.set_categories(categories);
+                    app.highlight_category(&category);
+                    Some(UiEvent::Tick)
+                } else {
+                    app.notification = Some(Notification::CategoryNotCreated(category));
+                    Some(UiEvent::Redraw)
+                }
+            }
+            ApiEvent::EditCategory(category, save_path) => {
+                self.api
+                    .edit_category(CategoryPayload {
+                        category,
+                        save_path,
+                    })
+                    .await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::RemoveCategory(category) => {
+                self.api.remove_categories(&[&category]).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::CreateTags(tags) => {
+                self.api.create_tags(&tags).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::DeleteTag(tag) => {
+                self.api.delete_tags(&[&tag]).await?;
+                Some(UiEvent::Tick)
+            }
+            // paused torrents stay paused after checking
+            ApiEvent::Recheck(hash) => {
+                self.api.recheck(&[&hash]).await?;
+                Some(UiEvent::Tick)
+            }
+            ApiEvent::RecheckAndResume(hash) => {
+                self.api.recheck(&[&hash]).await?;
+                let mut app = self.app.lock().await;
+                app.pending_rechecks
+                    .insert(hash, RecheckStep::Requested(Instant::now()));
+                Some(UiEvent::Tick)
+            }
         };
         {
             let mut app = self.app.lock().await;
             app.is_connected = true;
+            app.is_reconnecting = false;
             app.error_reconnection_attempt_n = 0;
         }
         if let Some(input_event) = input_event {
@@ -354,13 +1135,67 @@ impl ApiHandler {
         Ok(())
     }
 
+    async fn run_command(&self, args: Vec<String>) -> Option<UiEvent> {
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        match child {
+            Ok(child) => {
+                // do not block other events while the command is running
+                let app = Arc::clone(&self.app);
+                let ui_tx = self.ui_tx.clone();
+                tokio::spawn(async move {
+                    let output = match child.wait_with_output().await {
+                        Ok(output) => output,
+                        Err(e) => {
+                            tracing::warn!(?e);
+                            return;
+                        }
+                    };
+                    if output.status.success() {
+                        return;
+                    }
+
+                    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+                    let reason = if stderr.is_empty() {
+                        format!("{} exited with {}", args[0], output.status)
+                    } else {
+                        stderr
+                    };
+                    app.lock().await.notification = Some(Notification::CommandFailed(reason));
+                    let _ = ui_tx.send(UiEvent::Redraw).await;
+                });
+                None
+            }
+            Err(e) => {
+                let mut app = self.app.lock().await;
+                app.notification = Some(Notification::CommandFailed(format!("{}: {e}", args[0])));
+                Some(UiEvent::Redraw)
+            }
+        }
+    }
+
     pub async fn handle_error(&mut self, e: ApiError) {
+        let hint = e.hint();
         match e {
             ApiError::External(inner) => {
                 tracing::warn!(?inner);
+                self.was_disconnected = true;
                 let mut app = self.app.lock().await;
+                // reconnecting won't help, so the reason is shown
+                app.connection_error = match inner {
+                    ExternalError::WrongUrl(_) => Some(format!("{inner}. {hint}")),
+                    _ => None,
+                };
                 app.is_connected = false;
+                app.is_reconnecting = false;
                 app.error_reconnection_attempt_n += 1;
+                app.next_reconnection_ts =
+                    Instant::now() + reconnection_backoff(app.error_reconnection_attempt_n);
                 app.current_route = Route::Torrents;
             }
             ApiError::NotAuthenticated => {
@@ -370,43 +1205,143 @@ impl ApiHandler {
                         return;
                     }
                 }
+                // a transient 403, the cookie may still be valid
+                match self.handle(self.current_event.clone()).await {
+                    Ok(()) => {
+                        tracing::warn!("Retried without relogin");
+                        return;
+                    }
+                    Err(ApiError::NotAuthenticated) => {}
+                    Err(retry_error) => {
+                        // the next sync reports it
+                        tracing::warn!(?retry_error);
+                        return;
+                    }
+                }
                 tracing::warn!("Handling new session...");
-                if self.api.login().await.is_err() {
-                    let mut app = self.app.lock().await;
-                    app.is_running = false;
-                    app.forced_shutdown_reason = Some("Could not relogin".to_owned());
+                if !self.api.has_credentials() {
+                    self.require_login(format!("Authentication is required. {}", e.hint()))
+                        .await;
                     tracing::warn!("New session was not handled!");
                     return;
                 }
-                if self.handle(self.current_event.clone()).await.is_err() {
+                // relogins in a row back off, qBittorrent bans the IP otherwise
+                if let Some(last_relogin_ts) = self.last_relogin_ts {
+                    if last_relogin_ts.elapsed() > RELOGIN_RESET_AFTER {
+                        self.relogin_attempt_n = 0;
+                    } else if last_relogin_ts.elapsed()
+                        < reconnection_backoff(self.relogin_attempt_n)
+                    {
+                        tracing::warn!("Relogin postponed");
+                        return;
+                    }
+                }
+                self.relogin_attempt_n += 1;
+                self.last_relogin_ts = Some(Instant::now());
+                let login_result = self.api.login().await;
+                if let Err(ApiError::Login(LoginError::TooManyAttempts)) = login_result {
+                    // wait like on a lost connection instead of shutting down
                     let mut app = self.app.lock().await;
-                    app.is_running = false;
-                    app.forced_shutdown_reason = Some("Not authenticated".to_owned());
+                    app.is_connected = false;
+                    app.is_reconnecting = false;
+                    app.error_reconnection_attempt_n += 1;
+                    app.next_reconnection_ts =
+                        Instant::now() + reconnection_backoff(app.error_reconnection_attempt_n);
+                    app.current_route = Route::Torrents;
+                    tracing::warn!("Too many relogins, backing off");
+                    return;
+                } else if let Err(login_error) = login_result {
+                    self.require_login(format!(
+                        "Could not relogin: {login_error}. {}",
+                        login_error.hint()
+                    ))
+                    .await;
+                    tracing::warn!("New session was not handled!");
+                    return;
+                }
+                if let Err(handle_error) = self.handle(self.current_event.clone()).await {
+                    tracing::warn!(?handle_error);
+                    self.require_login(format!(
+                        "Not authenticated after relogin: {handle_error}. {}",
+                        handle_error.hint()
+                    ))
+                    .await;
                     tracing::warn!("New session was not handled!");
                     return;
                 };
                 tracing::warn!("New session was successfully handled!");
             }
-            ApiError::Login(_) => unreachable!(),
+            ApiError::NotFound => {
+                // the torrent was removed in the meantime, still connected
+                tracing::warn!("Torrent not found");
+                let mut app = self.app.lock().await;
+                app.notification = Some(Notification::TorrentNotFound);
+                app.current_torrent_peers = None;
+                if app.current_route == Route::Peers {
+                    app.current_route = Route::Torrents;
+                }
+            }
+            ApiError::Login(LoginError::WrongCredentials) => {
+                // retrying with the same credentials would only get the IP banned
+                self.require_login(format!(
+                    "Could not log in: {}. {}",
+                    LoginError::WrongCredentials,
+                    e.hint()
+                ))
+                .await;
+            }
+            ApiError::Login(inner) => {
+                // only on manual reconnection, wait for the next attempt
+                tracing::warn!(?inner);
+                let mut app = self.app.lock().await;
+                app.is_reconnecting = false;
+                app.next_reconnection_ts =
+                    Instant::now() + reconnection_backoff(app.error_reconnection_attempt_n);
+            }
         }
     }
 
+    /// Disconnected until new credentials are entered, instead of shutting down
+    async fn require_login(&self, reason: String) {
+        let mut app = self.app.lock().await;
+        app.is_connected = false;
+        app.is_reconnecting = false;
+        app.login_error = Some(reason);
+        app.current_route = Route::Torrents;
+    }
+
     pub async fn reload(&self) -> Result<(), ApiError> {
         match try_join!(
             self.api.transfer_info(),
             self.api.torrents_info(None),
             self.api.categories(),
+            self.api.tags(),
             self.api.transfer_speed_limits_mode(),
+            self.api.preferences(),
         ) {
-            Ok((transfer_info, torrents_info, categories, transfer_speed_limits_mode)) => {
+            Ok((
+                transfer_info,
+                torrents_info,
+                categories,
+                mut tags,
+                transfer_speed_limits_mode,
+                preferences,
+            )) => {
                 let mut app = self.app.lock().await;
+                let selected_hash = app.get_selected_torrent().map(|t| t.hash.clone());
                 app.torrents = torrents_info;
                 app.transfer_info = transfer_info;
                 app.transfer_info.use_alt_speed_limits =
                     transfer_speed_limits_mode == SpeedLimitsMode::Alternative;
-                let mut categories: Vec<String> = categories.into_keys().collect();
-                categories.sort_by_key(|a| a.to_lowercase());
-                app.categories = categories;
+                app.preferences = preferences;
+                app.category_save_paths = categories
+                    .values()
+                    .map(|c| (c.name.clone(), c.save_path.clone()))
+                    .collect();
+                app.set_categories(categories.into_keys().collect());
+                sort_names(&mut tags);
+                app.tags = tags;
+                app.reselect_torrent(selected_hash);
                 Ok(())
             }
             Err(e) => Err(e),
@@ -414,20 +1349,41 @@ impl ApiHandler {
     }
 
     pub async fn sync(&mut self) -> Result<(), ApiError> {
-        let data = self
+        let mut data = self
             .api
             .sync_maindata(GetMainDataParams { rid: self.rid })
             .await?;
 
+        // a restarted server does not know the last rid and sends everything
+        let is_restarted = self.was_disconnected && self.rid != 0 && data.full_update == Some(true);
+        if is_restarted {
+            tracing::warn!("Server restarted");
+            // the rids belong to the old server, start over from a full update
+            self.rid = 0;
+            self.peers_rid = 0;
+            data = self
+                .api
+                .sync_maindata(GetMainDataParams { rid: self.rid })
+                .await?;
+        }
+        self.was_disconnected = false;
         self.rid = data.rid;
 
         if let Some(full_update) = data.full_update {
             if full_update {
                 self.reload().await?;
+                if is_restarted {
+                    self.app.lock().await.notification = Some(Notification::ServerRestarted);
+                }
                 return Ok(());
             }
         }
 
+        let selected_hash = {
+            let app = self.app.lock().await;
+            app.get_selected_torrent().map(|t| t.hash.clone())
+        };
+
         if let Some(torrents_removed) = data.torrents_removed {
             let mut app = self.app.lock().await;
             app.torrents
@@ -457,11 +1413,19 @@ impl ApiHandler {
                     replace_if_some!(eta);
                     replace_if_some!(name);
                     replace_if_some!(progress);
+                    replace_if_some!(ratio);
+                    replace_if_some!(max_ratio);
                     replace_if_some!(save_path);
+                    replace_if_some!(seeding_time);
+                    replace_if_some!(max_seeding_time);
                     replace_if_some!(state);
                     replace_if_some!(size);
+                    replace_if_some!(total_size);
+                    replace_if_some!(tags);
+                    replace_if_some!(uploaded);
                     replace_if_some!(dlspeed);
                     replace_if_some!(upspeed);
+                    replace_if_some!(up_limit);
                 } else {
                     // new torrent?
                     should_reload = true;
@@ -470,30 +1434,55 @@ impl ApiHandler {
             }
         }
 
+        // both new and changed categories
         if let Some(categories) = data.categories {
             let mut app = self.app.lock().await;
+            let mut new_categories = app.categories.clone();
+            for (category, info) in categories {
+                app.category_save_paths
+                    .insert(category.clone(), info.save_path);
+                if !new_categories.contains(&category) {
+                    new_categories.push(category);
+                }
+            }
+            app.set_categories(new_categories);
+        }
 
-            // NOTE: or just reload if it doen't work
-            let new_categories: Vec<String> = categories.into_keys().collect();
-            app.categories.extend_from_slice(&new_categories);
-            app.categories.sort_unstable();
+        self.app.lock().await.reselect_torrent(selected_hash);
+
+        {
+            let mut app = self.app.lock().await;
+            if app.current_route == Route::Errored {
+                app.check_errored_data();
+            }
         }
 
         if let Some(categories_removed) = data.categories_removed {
             let mut app = self.app.lock().await;
+            app.category_save_paths
+                .retain(|c, _| !categories_removed.contains(c));
+            let new_categories = app
+                .categories
+                .iter()
+                .filter(|c| !categories_removed.contains(c))
+                .cloned()
+                .collect();
+            app.set_categories(new_categories);
+        }
 
-            let selected_category_name = match app.selected_category {
-                SelectedCategory::Category(i) => Some(&app.categories[i - 2]),
-                _ => None,
-            };
-            if let Some(selected_category_name) = selected_category_name {
-                if categories_removed.contains(selected_category_name) {
-                    app.selected_category = SelectedCategory::All;
+        if let Some(tags) = data.tags {
+            let mut app = self.app.lock().await;
+            for tag in tags {
+                if !app.tags.contains(&tag) {
+                    app.tags.push(tag);
                 }
             }
+            sort_names(&mut app.tags);
+        }
 
-            app.categories.retain(|c| !categories_removed.contains(c));
-            app.categories.sort_unstable();
+        if let Some(tags_removed) = data.tags_removed {
+            let mut app = self.app.lock().await;
+            app.tags.retain(|t| !tags_removed.contains(t));
         }
 
         if should_reload {
@@ -523,4 +1512,199 @@ impl ApiHandler {
 
         Ok(())
     }
+
+    pub async fn advance_pending_rechecks(&self) -> Result<(), ApiError> {
+        let hashes_to_resume = {
+            let mut app = self.app.lock().await;
+            let app = &mut *app;
+            if app.pending_rechecks.is_empty() {
+                return Ok(());
+            }
+
+            let torrents = &app.torrents;
+            let mut hashes_to_resume = vec![];
+            app.pending_rechecks.retain(|hash, step| {
+                let state = match torrents.iter().find(|t| &t.hash == hash) {
+                    Some(torrent) => torrent.state,
+                    None => return false, // deleted
+                };
+                let is_done = match step {
+                    RecheckStep::Requested(ts) => {
+                        if state.is_checking() {
+                            *step = RecheckStep::Checking;
+                            false
+                        } else {
+                            // small torrents are checked before the next sync
+                            ts.elapsed() >= RECHECK_START_TIMEOUT
+                        }
+                    }
+                    RecheckStep::Checking => !state.is_checking(),
+                };
+                if is_done {
+                    hashes_to_resume.push(hash.clone());
+                }
+                !is_done
+            });
+            hashes_to_resume
+        };
+
+        if !hashes_to_resume.is_empty() {
+            let hashes: Vec<&str> = hashes_to_resume.iter().map(String::as_str).collect();
+            self.api.resume(&hashes).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn run_on_complete_actions(&self) {
+        let mut commands = vec![];
+        {
+            let mut app = self.app.lock().await;
+            let app = &mut *app;
+
+            let mut completed_torrents = HashMap::with_capacity(app.torrents.len());
+            for torrent in &app.torrents {
+                let is_completed = StateFilter::Completed.matches(torrent);
+                // torrents that are already completed on start or when added are skipped
+                let was_completed = app.completed_torrents.get(&torrent.hash);
+                completed_torrents.insert(torrent.hash.clone(), is_completed);
+                if !is_completed || was_completed != Some(&false) {
+                    continue;
+                }
+
+                let completed_at = if torrent.completion_on > 0 {
+                    torrent.completion_on
+                } else {
+                    unix_now()
+                };
+                app.recently_completed.push_front(CompletedTorrent {
+                    hash: torrent.hash.clone(),
+                    name: torrent.name.clone(),
+                    completed_at,
+                });
+                app.recently_completed.truncate(RECENTLY_COMPLETED_MAX);
+                app.unseen_completed_n += 1;
+
+                let action = match app.config.on_complete_action(&torrent.category) {
+                    Some(action) => action,
+                    None => continue,
+                };
+                tracing::debug!(hash = torrent.hash, action, "Torrent completed");
+                match app.config.commands.get(action) {
+                    Some(template) => match build_command_args(template, torrent) {
+                        Ok(args) if !args.is_empty() => commands.push(args),
+                        Ok(_) => {
+                            app.notification = Some(Notification::CommandFailed(format!(
+                                "Command \"{action}\" is empty"
+                            )))
+                        }
+                        Err(e) => {
+                            app.notification = Some(Notification::CommandFailed(format!(
+                                "Command \"{action}\": {e}"
+                            )))
+                        }
+                    },
+                    None if action == "open" => {
+                        let path = app
+                            .config
+                            .rewrite_path(&torrent.content_path, &torrent.category);
+                        if path.exists() {
+                            if let Err(reason) = open_path(&app.config, &path) {
+                                app.notification = Some(Notification::CommandFailed(reason));
+                            }
+                        }
+                    }
+                    None => {
+                        app.notification = Some(Notification::CommandFailed(format!(
+                            "Unknown on complete command \"{action}\""
+                        )))
+                    }
+                }
+            }
+            app.completed_torrents = completed_torrents;
+        }
+
+        for args in commands {
+            self.run_command(args).await;
+        }
+    }
+
+    pub async fn check_data_cap(&self) -> Result<(), ApiError> {
+        let should_enable_alt_speed_limits = {
+            let mut app = self.app.lock().await;
+            let data_cap = match app.config.data_cap {
+                Some(ref data_cap) => data_cap,
+                None => return Ok(()),
+            };
+            // session data is reset when qBittorrent restarts
+            let is_exceeded = data_cap.is_exceeded(app.transfer_info.session_data());
+            if is_exceeded == app.is_data_cap_exceeded {
+                return Ok(());
+            }
+
+            let should_enable_alt_speed_limits = is_exceeded
+                && data_cap.enable_alt_speed_limits
+                && !app.transfer_info.use_alt_speed_limits;
+            if is_exceeded {
+                tracing::warn!("Data cap exceeded");
+            }
+            app.is_data_cap_exceeded = is_exceeded;
+            should_enable_alt_speed_limits
+        };
+
+        if should_enable_alt_speed_limits {
+            self.api.toggle_speed_limits_mode().await?;
+            self.app.lock().await.transfer_info.use_alt_speed_limits = true;
+        }
+
+        Ok(())
+    }
+
+    pub async fn sync_peers(&mut self) -> Result<(), ApiError> {
+        let hash = match self.peers_hash {
+            Some(ref hash) => hash.clone(),
+            None => return Ok(()),
+        };
+
+        {
+            let app = self.app.lock().await;
+            if app.current_route != Route::Peers || app.current_torrent_peers.is_none() {
+                self.peers_hash = None;
+                self.peers_rid = 0;
+                return Ok(());
+            }
+        }
+
+        let data = self
+            .api
+            .sync_torrent_peers(GetTorrentPeersParams {
+                hash,
+                rid: self.peers_rid,
+            })
+            .await?;
+
+        self.peers_rid = data.rid;
+
+        let mut app = self.app.lock().await;
+        let peers = match app.current_torrent_peers {
+            Some(ref mut peers) => peers,
+            None => return Ok(()),
+        };
+
+        if let Some(true) = data.full_update {
+            peers.clear();
+        }
+
+        if let Some(peers_removed) = data.peers_removed {
+            peers.retain(|key, _| !peers_removed.contains(key));
+        }
+
+        if let Some(updated_peers) = data.peers {
+            for (key, info) in updated_peers {
+                peers.entry(key).or_default().update(info);
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -1,4 +1,10 @@
-use std::time::SystemTime;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
 
 use crossterm::event::{KeyEvent, MouseEvent};
 use tokio::sync::mpsc::Sender;
@@ -9,8 +15,16 @@ use tui::{
 
 use crate::{
     api::ApiEvent,
+    clipboard,
+    config::Config,
     handlers,
-    model::{TorrentFile, TorrentInfo, TransferInfo},
+    history::AddHistory,
+    input::TextInput,
+    model::{
+        split_tags, AddedGroup, CategoryGroup, PeerCountsMode, PeerInfo, Preferences, StateFilter,
+        StateGroup, TableRow, TorrentFile, TorrentInfo, TorrentProperties, TrackerInfo,
+        TransferInfo,
+    },
 };
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -24,6 +38,61 @@ pub enum Route {
     Info,
     Files,
     Dialog,
+    Commands,
+    Peers,
+    Tags,
+    GoTo,
+    SetCategory,
+    NewCategory,
+    Manage,
+    Preferences,
+    AddTorrent,
+    Ratio,
+    AddTrackers,
+    Inbox,
+    Login, // only while disconnected, see login_error
+    Completed,
+    Errored,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddTorrentField {
+    #[default]
+    Source, // magnet, URL or path
+    Category,
+    SavePath,
+}
+
+impl AddTorrentField {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Source => Self::Category,
+            Self::Category => Self::SavePath,
+            Self::SavePath => Self::Source,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            Self::Source => Self::SavePath,
+            Self::Category => Self::Source,
+            Self::SavePath => Self::Category,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ManageFocus {
+    #[default]
+    Categories,
+    Tags,
+}
+
+#[derive(Debug)]
+pub enum ManagePrompt {
+    NewCategory,
+    EditCategory(String), // category name, the input is the save path
+    NewTags,
 }
 
 #[derive(Debug, Default)]
@@ -42,25 +111,135 @@ pub struct AppListState {
 pub struct ScrollableTextState {
     pub scroll: u16,
     pub text_height: usize,
+    pub view_height: u16, // inside the borders
+    pub is_wrapped: bool,
+    pub search: TextInput,
+    pub is_searching: bool,         // typing the search
+    pub search_matches: Vec<usize>, // line indexes, from the last draw
+    pub current_match: Option<usize>,
+    pub should_jump: bool, // to the first match below the top line on the next draw
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+impl ScrollableTextState {
+    /// Last line stays at the bottom of the view
+    pub fn max_scroll(&self) -> u16 {
+        (self.text_height as u16).saturating_sub(self.view_height)
+    }
+
+    pub fn scroll_down(&mut self, n: u16) {
+        self.scroll = std::cmp::min(self.scroll.saturating_add(n), self.max_scroll());
+    }
+
+    pub fn scroll_up(&mut self, n: u16) {
+        self.scroll = self.scroll.saturating_sub(n);
+    }
+
+    /// Called on draw, the text and the view can change between key presses
+    pub fn resize(&mut self, text_height: usize, view_height: u16) {
+        self.text_height = text_height;
+        self.view_height = view_height;
+        self.scroll = std::cmp::min(self.scroll, self.max_scroll());
+    }
+
+    /// Called on draw with the lines that contain the search
+    pub fn set_search_matches(&mut self, matches: Vec<usize>) {
+        self.search_matches = matches;
+        if self.should_jump {
+            self.should_jump = false;
+            let scroll = self.scroll as usize;
+            let i = self.search_matches.iter().position(|&line| line >= scroll);
+            self.select_match(i.or(Some(0)));
+        } else if self
+            .current_match
+            .is_some_and(|i| i >= self.search_matches.len())
+        {
+            self.current_match = None;
+        }
+    }
+
+    pub fn next_match(&mut self) {
+        let len = self.search_matches.len();
+        let i = self.current_match.map_or(0, |i| (i + 1) % len.max(1));
+        self.select_match(Some(i));
+    }
+
+    pub fn prev_match(&mut self) {
+        let len = self.search_matches.len();
+        let i = self
+            .current_match
+            .map_or(len.saturating_sub(1), |i| (i + len - 1) % len.max(1));
+        self.select_match(Some(i));
+    }
+
+    fn select_match(&mut self, i: Option<usize>) {
+        self.current_match = i.filter(|&i| i < self.search_matches.len());
+        if let Some(i) = self.current_match {
+            let line = self.search_matches[i] as u16;
+            self.scroll = std::cmp::min(line, self.max_scroll());
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum SelectedCategory {
     #[default]
     All,
     Uncategorized,
     Category(usize),
+    Many(BTreeSet<String>), // names, empty is uncategorized
 }
 
 #[derive(Debug)]
 pub enum Action {
     Delete,
     DeleteFiles,
+    PauseCategory(SelectedCategory),
+    RemoveCategory(String),
+    DeleteTag(String),
+    JumpToDuplicate(String),    // hash of the torrent that is already added
+    ForceStartAll(Vec<String>), // hashes in view when asked
+    CopyNames(Vec<String>),     // names in view, too many to copy without asking
+    AddTrackersToAll(Vec<String>, Vec<String>), // hashes in view when asked, tracker urls
+}
+
+// this session only, older ones are dropped
+pub const RECENTLY_COMPLETED_MAX: usize = 50;
+
+/// Seen completing during the session, for the completed feed
+#[derive(Debug)]
+pub struct CompletedTorrent {
+    pub hash: String,
+    pub name: String,
+    pub completed_at: i64, // unix timestamp
+}
+
+/// Recheck, then resume once checking is over
+#[derive(Debug)]
+pub enum RecheckStep {
+    Requested(Instant), // checking is not visible yet
+    Checking,
 }
 
 #[derive(Debug)]
 pub enum Notification {
     FileNotFound,
+    TorrentNotFound,
+    UnknownCategory(String),
+    CommandFailed(String),
+    NoErroredTorrents,
+    ResumedTorrents(usize),
+    ForceStartedTorrents(usize),
+    Reannounced(usize),
+    ServerRestarted,
+    AddTorrentFailed,
+    CategoryNotCreated(String),
+    ReadTorrentFileFailed(String),
+    InvalidTrackerUrl(String),
+    AddedTrackers(usize),
+    EmptyInbox,
+    InboxFailed(String),
+    CopiedNames(usize),
+    UploadLimitSet(i64), // bytes/s, 0 if none
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -69,6 +248,111 @@ pub enum SortOrder {
     Desc,
 }
 
+/// Case-insensitive, same order after a reload and after a sync
+pub fn sort_names(names: &mut [String]) {
+    names.sort_by(|a, b| cmp_names(a, b));
+}
+
+/// Order of `sort_names`, names that differ only in case stay apart
+pub fn cmp_names(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b))
+}
+
+/// Splits "a, b, c" into ("a, b,", " c")
+pub fn split_last_tag(value: &str) -> (&str, &str) {
+    match value.rfind(',') {
+        Some(i) => value.split_at(i + 1),
+        None => ("", value),
+    }
+}
+
+/// 2s, 4s, 8s, 16s, then every 30s
+pub fn reconnection_backoff(attempt_n: usize) -> Duration {
+    let secs = 1_u64 << std::cmp::min(attempt_n, 5);
+    Duration::from_secs(std::cmp::min(secs, 30))
+}
+
+/// File names are relative to the torrent's parent folder
+pub fn torrent_file_path(config: &Config, torrent: &TorrentInfo, file: &TorrentFile) -> PathBuf {
+    let content_path = config.rewrite_path(&torrent.content_path, &torrent.category);
+    content_path
+        .parent()
+        .unwrap_or(&content_path)
+        .join(&file.name)
+}
+
+/// Multiline cells make the row taller
+pub fn row_height(item: &[String]) -> u16 {
+    let line_breaks = item
+        .iter()
+        .map(|content| content.chars().filter(|c| *c == '\n').count())
+        .max()
+        .unwrap_or(0);
+    line_breaks as u16 + 1
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Byte range of the first case-insensitive match in the name,
+/// the values are lowercase, see `App::search_values`
+pub fn find_search_match(name: &str, normal: &str, dotted: &str) -> Option<Range<usize>> {
+    // lowercase can change the length, so every lowered byte keeps its char in the name
+    let mut lower = String::with_capacity(name.len());
+    let mut origins: Vec<Range<usize>> = Vec::with_capacity(name.len());
+    for (i, c) in name.char_indices() {
+        for lower_c in c.to_lowercase() {
+            lower.push(lower_c);
+            origins.extend((0..lower_c.len_utf8()).map(|_| i..i + c.len_utf8()));
+        }
+    }
+
+    // the earliest match, the normal value first at the same position
+    let (start, len) = [normal, dotted]
+        .iter()
+        .filter_map(|needle| lower.find(needle).map(|start| (start, needle.len())))
+        .min_by_key(|(start, _)| *start)?;
+
+    let name_start = origins.get(start).map_or(name.len(), |origin| origin.start);
+    let name_end = match len {
+        0 => name_start,
+        _ => origins[start + len - 1].end,
+    };
+    Some(name_start..name_end)
+}
+
+/// Index of the row under the click, rows are drawn from `offset`
+/// below the border and the header with its margin
+pub fn table_row_at(
+    click_row: u16,
+    rect: Rect,
+    header_height: u16,
+    row_heights: &[u16],
+    offset: usize,
+) -> Option<usize> {
+    let rows_start = rect.y + 1 + header_height; // top border
+    let rows_end = rect.y + rect.height.saturating_sub(1); // bottom border
+    if click_row < rows_start || click_row >= rows_end {
+        return None;
+    }
+
+    let mut y = rows_start;
+    for (i, height) in row_heights.iter().enumerate().skip(offset) {
+        if y >= rows_end {
+            break;
+        }
+        if click_row < y + height {
+            return Some(i);
+        }
+        y += height;
+    }
+
+    None
+}
+
 pub fn next_sort_order(curr: &Option<SortOrder>) -> Option<SortOrder> {
     match curr {
         Some(SortOrder::Asc) => Some(SortOrder::Desc),
@@ -81,52 +365,134 @@ pub fn next_sort_order(curr: &Option<SortOrder>) -> Option<SortOrder> {
 pub struct App {
     pub host: String,
     pub api_tx: Sender<ApiEvent>,
+    pub config: Config,
 
     pub is_connected: bool,
+    pub connection_error: Option<String>, // instead of the generic message, e.g. a wrong url
     pub is_running: bool,
     pub forced_shutdown_reason: Option<String>,
+    pub login_error: Option<String>, // the session could not be renewed, no reconnection until login
+    pub login_username: TextInput,
+    pub login_password: TextInput, // cleared once it is sent
+    pub is_login_password_focused: bool,
+
+    pub last_input_ts: Instant, // for idle_after_minutes
 
     pub error_reconnection_attempt_n: usize,
+    pub next_reconnection_ts: Instant,
+    pub is_reconnecting: bool,
 
     pub notification: Option<Notification>,
+    pub is_data_cap_exceeded: bool,
 
     pub torrents: Vec<TorrentInfo>,
-    pub current_torrent: Option<TorrentInfo>, // for files and info
+    pub current_torrent: Option<TorrentInfo>, // for files, info and peers
+    pub current_torrent_properties: Option<TorrentProperties>, // for info
+    pub current_torrent_trackers: Option<Vec<TrackerInfo>>, // for info
     pub transfer_info: TransferInfo,
+    pub preferences: Preferences,
     pub categories: Vec<String>,
+    pub category_save_paths: HashMap<String, String>, // empty is the default one
+    pub tags: Vec<String>,
 
     pub current_route: Route,
     pub on_help_route: Option<Route>,
     pub on_error_route: Option<Route>,
 
     pub selected_category: SelectedCategory,
+    pub smart_filter: Option<String>, // name from config
+    pub state_filter: StateFilter,
+    pub hide_fetching_metadata: bool,
+    pub add_torrent_input: TextInput,
+    pub add_category_input: TextInput,
+    pub add_save_path_input: TextInput,
+    pub add_torrent_field: AddTorrentField,
+    pub add_history: AddHistory,
+    pub add_history_pos: Option<usize>, // recalled entry, None while typing
+    pub add_paused: bool,
+    pub add_trackers_input: TextInput, // urls separated with spaces
+    pub add_trackers_hashes: Vec<String>, // torrents in view when the prompt was opened
+    pub no_working_tracker: Option<HashSet<String>>, // hashes, from the last trackers scan
+    pub only_no_working_tracker: bool,
 
     pub torrents_table: TorrentsTable,
     pub torrents_table_rect: Option<Rect>,
+    pub speed_limits_rect: Option<Rect>, // stats bar segment, click to toggle
 
-    pub categories_list: AppListState,
+    pub categories_list: AppListState, // the selection is an index in all categories
     pub categories_list_rect: Option<Rect>,
+    pub categories_view: ListState, // the filtered list as drawn, for the offset
+    pub categories_filter: TextInput,
+    pub is_filtering_categories: bool, // typing goes to the filter
+    pub is_creating_category: bool,    // typing goes to the new category prompt
+    pub new_category_name: TextInput,
+    pub new_category_save_path: TextInput, // empty for the default one
+    pub is_new_category_save_path_focused: bool,
+    pub editing_category: Option<String>, // typing goes to the save path prompt of the category
+    pub category_save_path: TextInput,
 
     pub info_state: ScrollableTextState,
+    pub preferences_state: ScrollableTextState,
 
     pub current_torrent_files: Option<Vec<TorrentFile>>,
     pub files_list: AppListState,
+    pub inbox_files: Vec<PathBuf>,
+    pub inbox_list: AppListState,
     pub files_list_rect: Option<Rect>,
 
-    pub search_value: String,
+    pub current_torrent_peers: Option<HashMap<String, PeerInfo>>,
+    pub peers_table: TorrentsTable,
+
+    pub search_input: TextInput,
+
+    pub goto_input: TextInput,
+
+    pub tags_input: TextInput,
+    pub tags_completion: Option<(String, usize)>, // typed prefix, suggestion index
 
     pub help_state: ScrollableTextState,
 
     pub current_action: Option<Action>,
     pub confirm: bool,
 
+    pub show_summary: bool,
+    pub group_by_added: bool,
+    pub group_by_category: bool,
+    pub should_clear_screen: bool,       // before the next draw
+    pub pending_hashes: HashSet<String>, // torrents with an action in flight
+    pub collapsed_categories: HashSet<String>,
+    pub compact: bool,
+    pub peer_counts_mode: PeerCountsMode,
+    pub show_hash_column: bool,
+    pub pending_rechecks: HashMap<String, RecheckStep>, // by hash
+    pub completed_torrents: HashMap<String, bool>,      // by hash, for on complete actions
+    pub recently_completed: VecDeque<CompletedTorrent>, // newest first
+    pub unseen_completed_n: usize,                      // since the feed was last opened
+    pub completed_list: AppListState,
+    pub errored_list: AppListState,
+    pub errored_location_input: TextInput,
+    pub is_editing_errored_location: bool,
+    pub errored_not_found: HashSet<String>, // by hash, data not found locally
+
     pub category_sort_order: Option<SortOrder>,
     pub name_sort_order: Option<SortOrder>,
     pub status_sort_order: Option<SortOrder>,
 
     pub sort_list: AppListState,
+    pub hidden_state_groups: HashSet<StateGroup>,
     pub sort_list_rect: Option<Rect>,
 
+    pub commands_list: AppListState,
+
+    pub set_category_list: AppListState, // "" is uncategorized
+    pub new_category_input: TextInput,
+
+    pub manage_focus: ManageFocus,
+    pub manage_categories_list: AppListState,
+    pub manage_tags_list: AppListState,
+    pub manage_prompt: Option<ManagePrompt>,
+    pub manage_input: TextInput,
+
     pub left_click: (u16, u16),
     pub left_click_ts: SystemTime,
 
@@ -135,59 +501,146 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(host: &str, api_tx: Sender<ApiEvent>) -> Self {
+    pub fn new(host: &str, api_tx: Sender<ApiEvent>, config: Config) -> Self {
         let mut categories_list = AppListState::default();
         categories_list.state.select(Some(0)); // select "All" by default
+        let compact = config.compact;
+        let add_paused = config.add_paused;
 
         Self {
             host: host.to_owned(),
             api_tx,
+            config,
 
             is_connected: true,
+            connection_error: None,
             is_running: true,
             forced_shutdown_reason: None,
+            login_error: None,
+            login_username: TextInput::default(),
+            login_password: TextInput::default(),
+            is_login_password_focused: false,
+
+            last_input_ts: Instant::now(),
 
             error_reconnection_attempt_n: 0,
+            next_reconnection_ts: Instant::now(),
+            is_reconnecting: false,
 
             notification: None,
+            is_data_cap_exceeded: false,
 
             torrents: vec![],
             current_torrent: None,
+            current_torrent_properties: None,
+            current_torrent_trackers: None,
             transfer_info: TransferInfo::default(),
+            preferences: Preferences::default(),
             categories: vec![],
+            category_save_paths: HashMap::new(),
+            tags: vec![],
 
             current_route: Route::Torrents,
             on_help_route: None,
             on_error_route: None,
 
             selected_category: SelectedCategory::default(),
+            smart_filter: None,
+            state_filter: StateFilter::default(),
+            hide_fetching_metadata: false,
+            add_torrent_input: TextInput::default(),
+            add_category_input: TextInput::default(),
+            add_save_path_input: TextInput::default(),
+            add_torrent_field: AddTorrentField::default(),
+            add_history: AddHistory::load(AddHistory::default_path()),
+            add_history_pos: None,
+            add_paused,
+            add_trackers_input: TextInput::default(),
+            add_trackers_hashes: Vec::new(),
+            no_working_tracker: None,
+            only_no_working_tracker: false,
 
             torrents_table: TorrentsTable::default(),
             torrents_table_rect: None,
+            speed_limits_rect: None,
 
             categories_list,
             categories_list_rect: None,
-
-            info_state: ScrollableTextState::default(),
+            categories_view: ListState::default(),
+            categories_filter: TextInput::default(),
+            is_filtering_categories: false,
+            is_creating_category: false,
+            new_category_name: TextInput::default(),
+            new_category_save_path: TextInput::default(),
+            is_new_category_save_path_focused: false,
+            editing_category: None,
+            category_save_path: TextInput::default(),
+
+            info_state: ScrollableTextState {
+                is_wrapped: true,
+                ..ScrollableTextState::default()
+            },
+            preferences_state: ScrollableTextState::default(),
 
             current_torrent_files: None,
             files_list: AppListState::default(),
+            inbox_files: Vec::new(),
+            inbox_list: AppListState::default(),
             files_list_rect: None,
 
-            search_value: String::new(),
+            current_torrent_peers: None,
+            peers_table: TorrentsTable::default(),
+
+            search_input: TextInput::default(),
+
+            goto_input: TextInput::default(),
+
+            tags_input: TextInput::default(),
+            tags_completion: None,
 
             help_state: ScrollableTextState::default(),
 
             current_action: None,
             confirm: false,
 
+            show_summary: false,
+            group_by_added: false,
+            group_by_category: false,
+            should_clear_screen: false,
+            pending_hashes: HashSet::new(),
+            collapsed_categories: HashSet::new(),
+            compact,
+            peer_counts_mode: PeerCountsMode::default(),
+            show_hash_column: false,
+            pending_rechecks: HashMap::new(),
+            completed_torrents: HashMap::new(),
+            recently_completed: VecDeque::new(),
+            unseen_completed_n: 0,
+            completed_list: AppListState::default(),
+            errored_list: AppListState::default(),
+            errored_location_input: TextInput::default(),
+            is_editing_errored_location: false,
+            errored_not_found: HashSet::new(),
+
             category_sort_order: Some(SortOrder::Asc),
             name_sort_order: Some(SortOrder::Asc),
             status_sort_order: Some(SortOrder::Asc),
 
             sort_list: AppListState::default(),
+            hidden_state_groups: HashSet::new(),
             sort_list_rect: None,
 
+            commands_list: AppListState::default(),
+
+            set_category_list: AppListState::default(),
+            new_category_input: TextInput::default(),
+
+            manage_focus: ManageFocus::default(),
+            manage_categories_list: AppListState::default(),
+            manage_tags_list: AppListState::default(),
+            manage_prompt: None,
+            manage_input: TextInput::default(),
+
             left_click: (0, 0),
             left_click_ts: SystemTime::now(),
 
@@ -223,6 +676,51 @@ impl App {
             Route::Files => {
                 handlers::files::handle_key_event(event, self).await;
             }
+            Route::Commands => {
+                handlers::commands::handle_key_event(event, self).await;
+            }
+            Route::Peers => {
+                handlers::peers::handle_key_event(event, self).await;
+            }
+            Route::Tags => {
+                handlers::tags::handle_key_event(event, self).await;
+            }
+            Route::GoTo => {
+                handlers::goto::handle_key_event(event, self).await;
+            }
+            Route::SetCategory => {
+                handlers::set_category::handle_key_event(event, self).await;
+            }
+            Route::NewCategory => {
+                handlers::new_category::handle_key_event(event, self).await;
+            }
+            Route::Manage => {
+                handlers::manage::handle_key_event(event, self).await;
+            }
+            Route::Preferences => {
+                handlers::preferences::handle_key_event(event, self).await;
+            }
+            Route::AddTorrent => {
+                handlers::add_torrent::handle_key_event(event, self).await;
+            }
+            Route::Ratio => {
+                handlers::ratio::handle_key_event(event, self).await;
+            }
+            Route::AddTrackers => {
+                handlers::add_trackers::handle_key_event(event, self).await;
+            }
+            Route::Inbox => {
+                handlers::inbox::handle_key_event(event, self).await;
+            }
+            Route::Login => {
+                handlers::login::handle_key_event(event, self).await;
+            }
+            Route::Completed => {
+                handlers::completed::handle_key_event(event, self).await;
+            }
+            Route::Errored => {
+                handlers::errored::handle_key_event(event, self).await;
+            }
         }
     }
 
@@ -233,7 +731,48 @@ impl App {
 
     pub async fn handle_disconnected_key_event(&mut self, event: KeyEvent) {
         tracing::debug!("disconnected_key_event: {:?}", &event);
-        handlers::error::handle_key_event(event, self).await;
+        if self.current_route == Route::Login {
+            handlers::login::handle_key_event(event, self).await;
+        } else {
+            handlers::error::handle_key_event(event, self).await;
+        }
+    }
+
+    pub async fn handle_paste_event(&mut self, text: String) {
+        tracing::debug!("paste_event: {} chars", text.len());
+        match self.current_route {
+            Route::Search => self.search_input.insert_str(&text),
+            Route::Tags => {
+                self.tags_input.insert_str(&text);
+                self.tags_completion = None;
+            }
+            Route::NewCategory => self.new_category_input.insert_str(&text),
+            Route::Categories if self.is_creating_category => {
+                if self.is_new_category_save_path_focused {
+                    self.new_category_save_path.insert_str(&text);
+                } else {
+                    self.new_category_name.insert_str(&text);
+                }
+            }
+            Route::Categories if self.editing_category.is_some() => {
+                self.category_save_path.insert_str(&text);
+            }
+            Route::Categories if self.is_filtering_categories => {
+                self.categories_filter.insert_str(&text);
+                self.apply_categories_filter();
+            }
+            Route::AddTorrent => self.get_add_torrent_input().insert_str(&text),
+            Route::Info if self.info_state.is_searching => self.info_state.search.insert_str(&text),
+            Route::Errored if self.is_editing_errored_location => {
+                self.errored_location_input.insert_str(&text)
+            }
+            // one url per line is the usual format of tracker lists
+            Route::AddTrackers => self
+                .add_trackers_input
+                .insert_str(&text.replace(['\r', '\n'], " ")),
+            Route::Manage if self.manage_prompt.is_some() => self.manage_input.insert_str(&text),
+            _ => {}
+        }
     }
 
     pub async fn handle_mouse_event(&mut self, event: MouseEvent) {
@@ -252,9 +791,8 @@ impl App {
         }
     }
 
-    pub fn get_visible_torrents(&self) -> Vec<&TorrentInfo> {
-        // filter by category
-        let torrents: Vec<&TorrentInfo> = match self.selected_category {
+    pub fn get_category_torrents(&self, category: &SelectedCategory) -> Vec<&TorrentInfo> {
+        match category {
             SelectedCategory::All => self.torrents.iter().collect(),
             SelectedCategory::Uncategorized => self
                 .torrents
@@ -268,18 +806,49 @@ impl App {
                     .filter(|t| &t.category == category)
                     .collect()
             }
+            SelectedCategory::Many(names) => self
+                .torrents
+                .iter()
+                .filter(|t| names.contains(&t.category))
+                .collect(),
+        }
+    }
+
+    pub fn get_visible_torrents(&self) -> Vec<&TorrentInfo> {
+        // filter by category
+        let torrents = self.get_category_torrents(&self.selected_category);
+
+        // filter by smart filter
+        let torrents: Vec<&TorrentInfo> = match self
+            .smart_filter
+            .as_ref()
+            .and_then(|name| self.config.filters.get(name))
+        {
+            Some(smart_filter) => torrents
+                .into_iter()
+                .filter(|t| smart_filter.matches(t))
+                .collect(),
+            None => torrents,
         };
 
+        // filter by state
+        let torrents: Vec<&TorrentInfo> = torrents
+            .into_iter()
+            .filter(|t| self.state_filter.matches(t))
+            .filter(|t| !self.hidden_state_groups.contains(&StateGroup::of(t)))
+            .filter(|t| !(self.hide_fetching_metadata && t.state.is_fetching_metadata()))
+            .filter(|t| match self.no_working_tracker {
+                Some(ref hashes) if self.only_no_working_tracker => hashes.contains(&t.hash),
+                _ => true,
+            })
+            .collect();
+
         // filter by name
-        let normal_value = self.search_value.trim().to_lowercase();
-        let dotted_value = normal_value.split(' ').collect::<Vec<&str>>().join(".");
+        let (normal_value, dotted_value) = self.search_values();
 
         let mut res: Vec<&TorrentInfo> = torrents
             .into_iter()
-            .filter(|item| {
-                let torrent_name = item.name.to_lowercase();
-                torrent_name.contains(&normal_value) || torrent_name.contains(&dotted_value)
-            })
+            .filter(|item| find_search_match(&item.name, &normal_value, &dotted_value).is_some())
             .collect();
 
         // sort
@@ -310,36 +879,491 @@ impl App {
             }
         }
 
+        // stable, the sort order above is kept inside the groups
+        if self.group_by_added {
+            if self.name_sort_order.is_none()
+                && self.category_sort_order.is_none()
+                && self.status_sort_order.is_none()
+            {
+                res.sort_by(|a, b| b.added_on.cmp(&a.added_on));
+            }
+            let now = unix_now();
+            res.sort_by_key(|t| AddedGroup::from_timestamp(t.added_on, now));
+        }
+
+        if self.group_by_category {
+            match self.category_sort_order {
+                // the same order as in the categories list
+                Some(SortOrder::Desc) => res.sort_by(|a, b| cmp_names(&b.category, &a.category)),
+                _ => res.sort_by(|a, b| cmp_names(&a.category, &b.category)),
+            }
+        }
+
         res
     }
 
+    /// Visible torrents up to max_torrents, with a header before every
+    /// category or added date group when grouped, torrents of collapsed
+    /// categories are left out
+    pub fn get_table_rows(&self) -> Vec<TableRow<'_>> {
+        let mut torrents = self.get_visible_torrents();
+        if self.config.max_torrents > 0 {
+            torrents.truncate(self.config.max_torrents);
+        }
+        if self.group_by_added {
+            let now = unix_now();
+            let mut rows = Vec::with_capacity(torrents.len());
+            let mut last_group = None;
+            for torrent in torrents {
+                let group = AddedGroup::from_timestamp(torrent.added_on, now);
+                if last_group != Some(group) {
+                    rows.push(TableRow::Added(group));
+                    last_group = Some(group);
+                }
+                rows.push(TableRow::Torrent(torrent));
+            }
+            return rows;
+        }
+        if !self.group_by_category {
+            return torrents.into_iter().map(TableRow::Torrent).collect();
+        }
+
+        let mut rows = Vec::with_capacity(torrents.len());
+        let mut start = 0;
+        while start < torrents.len() {
+            let name = torrents[start].category.as_str();
+            let len = torrents[start..]
+                .iter()
+                .take_while(|t| t.category == name)
+                .count();
+            let group = &torrents[start..start + len];
+            let is_collapsed = self.collapsed_categories.contains(name);
+            rows.push(TableRow::Category(CategoryGroup {
+                name,
+                torrents_n: len,
+                dlspeed: group.iter().map(|t| t.dlspeed).sum(),
+                upspeed: group.iter().map(|t| t.upspeed).sum(),
+                is_collapsed,
+            }));
+            if !is_collapsed {
+                rows.extend(group.iter().copied().map(TableRow::Torrent));
+            }
+            start += len;
+        }
+
+        rows
+    }
+
+    /// Header row under the cursor
+    pub fn get_selected_category_group(&self) -> Option<String> {
+        let i = self.torrents_table.state.selected()?;
+        match self.get_table_rows().get(i) {
+            Some(TableRow::Category(group)) => Some(group.name.to_owned()),
+            _ => None,
+        }
+    }
+
+    pub fn toggle_category_group(&mut self, name: String) {
+        if !self.collapsed_categories.remove(&name) {
+            self.collapsed_categories.insert(name);
+        }
+    }
+
+    /// The selection is a row index, it is moved back to the same torrent
+    /// after the list changes, or kept within the table if the torrent is gone
+    pub fn reselect_torrent(&mut self, hash: Option<String>) {
+        match hash.and_then(|hash| self.torrent_row_index(&hash)) {
+            Some(i) => self.torrents_table.state.select(Some(i)),
+            None => self.ensure_selection(),
+        }
+    }
+
+    /// Row index of the torrent in the table, if it is shown
+    pub fn torrent_row_index(&self, hash: &str) -> Option<usize> {
+        self.get_table_rows()
+            .iter()
+            .position(|row| matches!(row, TableRow::Torrent(t) if t.hash == hash))
+    }
+
+    /// All errored torrents, filters do not apply, sorted by name
+    pub fn get_errored_torrents(&self) -> Vec<&TorrentInfo> {
+        let mut torrents: Vec<&TorrentInfo> = self
+            .torrents
+            .iter()
+            .filter(|t| t.state.is_errored())
+            .collect();
+        torrents.sort_by(|a, b| a.name.cmp(&b.name));
+        torrents
+    }
+
+    /// Errored torrents without their data here, checked when the errored
+    /// screen is opened and on sync, not on every frame
+    pub fn check_errored_data(&mut self) {
+        self.errored_not_found = self
+            .get_errored_torrents()
+            .into_iter()
+            .filter(|t| {
+                !self
+                    .config
+                    .rewrite_path(&t.content_path, &t.category)
+                    .exists()
+            })
+            .map(|t| t.hash.clone())
+            .collect();
+    }
+
+    /// Input of the focused field of the add form
+    pub fn get_add_torrent_input(&mut self) -> &mut TextInput {
+        match self.add_torrent_field {
+            AddTorrentField::Source => &mut self.add_torrent_input,
+            AddTorrentField::Category => &mut self.add_category_input,
+            AddTorrentField::SavePath => &mut self.add_save_path_input,
+        }
+    }
+
+    /// Empty form, the category is the selected one if there is exactly one
+    pub fn open_add_torrent(&mut self) {
+        let category = match self.selected_category {
+            SelectedCategory::Category(i) => self.categories.get(i - 2).cloned(),
+            _ => None,
+        };
+        self.add_torrent_input.clear();
+        self.add_category_input
+            .set_value(category.as_deref().unwrap_or_default());
+        self.add_save_path_input.clear();
+        self.add_torrent_field = AddTorrentField::Source;
+        self.add_history_pos = None;
+        self.current_route = Route::AddTorrent;
+    }
+
+    /// Row index of the first torrent in the table with the same category
+    pub fn first_row_of_category(&self, category: &str) -> Option<usize> {
+        self.get_table_rows()
+            .iter()
+            .position(|row| matches!(row, TableRow::Torrent(t) if t.category == category))
+    }
+
+    /// Next torrent in the table without connected peers after the selected row,
+    /// wraps around
+    pub fn next_row_without_peers(&self) -> Option<usize> {
+        let rows = self.get_table_rows();
+        let start = self.torrents_table.state.selected().map_or(0, |i| i + 1);
+        (0..rows.len())
+            .map(|offset| (start + offset) % rows.len())
+            .find(|&i| matches!(rows[i], TableRow::Torrent(t) if t.has_no_peers()))
+    }
+
+    /// Lowercase search input as typed and with dots instead of spaces
+    pub fn search_values(&self) -> (String, String) {
+        let normal_value = self.search_input.value().trim().to_lowercase();
+        let dotted_value = normal_value.split(' ').collect::<Vec<&str>>().join(".");
+        (normal_value, dotted_value)
+    }
+
+    /// None on a header row
     pub fn get_selected_torrent(&self) -> Option<&TorrentInfo> {
-        self.torrents_table
+        let i = self.torrents_table.state.selected()?;
+        match self.get_table_rows().get(i) {
+            Some(TableRow::Torrent(torrent)) => Some(torrent),
+            _ => None,
+        }
+    }
+
+    /// Latest synced state of the torrent opened in info/files/peers,
+    /// the snapshot is used if it's gone from the list
+    pub fn get_current_torrent(&self) -> Option<&TorrentInfo> {
+        let current = self.current_torrent.as_ref()?;
+        self.torrents
+            .iter()
+            .find(|t| t.hash == current.hash)
+            .or(Some(current))
+    }
+
+    /// Header with its bottom margin, if any
+    pub fn torrents_header_height(&self) -> u16 {
+        if self.compact {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Called whenever filters change what is shown, nothing if the table is empty
+    pub fn select_first_torrent(&mut self) {
+        let i = self
+            .get_table_rows()
+            .iter()
+            .position(TableRow::is_selectable);
+        self.torrents_table.state.select(i);
+    }
+
+    /// The row itself if it can be selected, the closest one below or above it otherwise
+    pub fn nearest_selectable_row(&self, i: usize) -> Option<usize> {
+        let rows = self.get_table_rows();
+        let i = std::cmp::min(i, rows.len().checked_sub(1)?);
+        (i..rows.len())
+            .chain((0..i).rev())
+            .find(|&i| rows[i].is_selectable())
+    }
+
+    /// Keeps the selection on a row that can be selected, near the first one
+    /// if nothing is selected
+    pub fn ensure_selection(&mut self) {
+        let i = self.torrents_table.state.selected().unwrap_or(0);
+        let i = self.nearest_selectable_row(i);
+        self.torrents_table.state.select(i);
+    }
+
+    pub async fn sync(&self) {
+        self.api_tx.send(ApiEvent::Sync).await.unwrap()
+    }
+
+    /// Skips the attempt while waiting for the reconnection backoff or for a login,
+    /// nothing is sent at all while idle
+    pub async fn sync_or_wait_for_reconnection(&self) {
+        if self.is_idle() {
+            return;
+        }
+
+        if self.is_connected
+            || (self.login_error.is_none() && Instant::now() >= self.next_reconnection_ts)
+        {
+            self.sync().await;
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        let minutes = self.config.idle_after_minutes;
+        minutes > 0 && self.last_input_ts.elapsed() >= Duration::from_secs(minutes * 60)
+    }
+
+    /// Returns true if the app was idle, the input only wakes it up then
+    pub async fn wake_up(&mut self) -> bool {
+        let was_idle = self.is_idle();
+        self.last_input_ts = Instant::now();
+        if was_idle {
+            self.sync_or_wait_for_reconnection().await;
+        }
+        was_idle
+    }
+
+    pub async fn reconnect_now(&mut self) {
+        if self.is_reconnecting {
+            return;
+        }
+
+        self.is_reconnecting = true;
+        self.next_reconnection_ts = Instant::now();
+        self.api_tx.send(ApiEvent::Reconnect).await.unwrap();
+    }
+
+    /// All -> first -> ... -> last -> All
+    pub fn next_smart_filter(&mut self) {
+        let mut names = self.config.filters.keys();
+        self.smart_filter = match self.smart_filter {
+            Some(ref current) => names.skip_while(|name| *name != current).nth(1).cloned(),
+            None => names.next().cloned(),
+        };
+        self.select_first_torrent();
+    }
+
+    pub fn category_name(&self, category: &SelectedCategory) -> &str {
+        match category {
+            SelectedCategory::All => "All",
+            SelectedCategory::Uncategorized => "Uncategorized",
+            SelectedCategory::Category(i) => &self.categories[i - 2],
+            SelectedCategory::Many(_) => "Selected categories",
+        }
+    }
+
+    pub fn selected_category_name(&self) -> &str {
+        self.category_name(&self.selected_category)
+    }
+
+    /// Existing tags matching the last comma-separated part of the tags input
+    pub fn get_tag_suggestions(&self) -> Vec<&String> {
+        let (head, last) = split_last_tag(self.tags_input.value());
+        let prefix = match self.tags_completion {
+            Some((ref prefix, _)) => prefix.clone(),
+            None => last.trim().to_lowercase(),
+        };
+        let entered = split_tags(head);
+
+        self.tags
+            .iter()
+            .filter(|tag| tag.to_lowercase().starts_with(&prefix))
+            .filter(|tag| !entered.contains(&tag.as_str()))
+            .collect()
+    }
+
+    /// Keeps the selected and highlighted categories (by name)
+    pub fn set_categories(&mut self, mut categories: Vec<String>) {
+        sort_names(&mut categories);
+
+        let category_name = |i: usize| -> Option<String> {
+            i.checked_sub(2)
+                .and_then(|i| self.categories.get(i))
+                .cloned()
+        };
+        let selected_name = match self.selected_category {
+            SelectedCategory::Category(i) => category_name(i),
+            _ => None,
+        };
+        let highlighted_name = self
+            .categories_list
             .state
             .selected()
-            .and_then(|i| self.get_visible_torrents().get(i).copied())
+            .and_then(category_name);
+
+        self.categories = categories;
+
+        let category_index = |name: &String| -> Option<usize> {
+            self.categories
+                .iter()
+                .position(|c| c == name)
+                .map(|i| i + 2)
+        };
+        if let Some(ref name) = selected_name {
+            self.selected_category = match category_index(name) {
+                Some(i) => SelectedCategory::Category(i),
+                None => SelectedCategory::All,
+            };
+        }
+        if let SelectedCategory::Many(ref mut names) = self.selected_category {
+            let categories = &self.categories;
+            names.retain(|name| name.is_empty() || categories.contains(name));
+            if names.is_empty() {
+                self.selected_category = SelectedCategory::All;
+            }
+        }
+        if let Some(ref name) = highlighted_name {
+            let i = category_index(name).unwrap_or(0);
+            self.categories_list.state.select(Some(i));
+        }
     }
 
-    pub fn select_first_torrent(&mut self) {
-        if self.get_visible_torrents().is_empty() {
+    /// Indexes in the categories list that match the filter,
+    /// "All" and "Uncategorized" are always shown
+    pub fn filtered_category_indexes(&self) -> Vec<usize> {
+        let filter = self.categories_filter.value().trim().to_lowercase();
+        let matching = self
+            .categories
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.to_lowercase().contains(&filter))
+            .map(|(i, _)| i + 2);
+        [0, 1].into_iter().chain(matching).collect()
+    }
+
+    /// Highlights the category in the list, the filter is cleared
+    /// so it can't be hidden
+    pub fn highlight_category(&mut self, name: &str) {
+        if let Some(i) = self.categories.iter().position(|c| c == name) {
+            self.categories_filter.clear();
+            self.categories_list.state.select(Some(i + 2));
+        }
+    }
+
+    /// Moves the highlight to the first match, unless it's on a match already
+    pub fn apply_categories_filter(&mut self) {
+        if self.categories_filter.is_empty() {
             return;
         }
 
-        self.torrents_table.state.select(Some(0));
+        let indexes = self.filtered_category_indexes();
+        let is_on_match = self
+            .categories_list
+            .state
+            .selected()
+            .is_some_and(|i| i >= 2 && indexes.contains(&i));
+        if !is_on_match {
+            let i = indexes.get(2).copied().unwrap_or(0);
+            self.categories_list.state.select(Some(i));
+        }
     }
 
-    pub async fn sync(&self) {
-        self.api_tx.send(ApiEvent::Sync).await.unwrap()
+    pub fn get_highlighted_category(&self) -> Option<SelectedCategory> {
+        self.categories_list.state.selected().map(|i| match i {
+            0 => SelectedCategory::All,
+            1 => SelectedCategory::Uncategorized,
+            i => SelectedCategory::Category(i),
+        })
+    }
+
+    /// Empty name is uncategorized
+    pub fn select_category_by_name(&mut self, name: &str) -> bool {
+        let i = if name.is_empty() {
+            1
+        } else {
+            match self.categories.iter().position(|c| c == name) {
+                Some(i) => i + 2,
+                None => return false,
+            }
+        };
+
+        self.selected_category = if i == 1 {
+            SelectedCategory::Uncategorized
+        } else {
+            SelectedCategory::Category(i)
+        };
+        self.categories_list.state.select(Some(i));
+        self.select_first_torrent();
+        true
+    }
+
+    /// Filters and search are cleared if they hide the torrent
+    pub fn jump_to_torrent(&mut self, hash: &str) {
+        if !self.get_visible_torrents().iter().any(|t| t.hash == hash) {
+            self.selected_category = SelectedCategory::All;
+            self.categories_list.state.select(Some(0));
+            self.smart_filter = None;
+            self.state_filter = StateFilter::All;
+            self.hide_fetching_metadata = false;
+            self.only_no_working_tracker = false;
+            self.search_input.clear();
+        }
+        if let Some(torrent) = self.torrents.iter().find(|t| t.hash == hash) {
+            self.collapsed_categories.remove(&torrent.category);
+        }
+
+        let i = self.torrent_row_index(hash);
+        self.torrents_table.state.select(i);
+    }
+
+    /// The table shows the union of the toggled categories, "All" resets them
+    pub fn toggle_highlighted_category(&mut self) {
+        let name = match self.categories_list.state.selected() {
+            Some(0) | None => {
+                self.selected_category = SelectedCategory::All;
+                self.select_first_torrent();
+                return;
+            }
+            Some(1) => String::new(),
+            Some(i) => self.categories[i - 2].clone(),
+        };
+
+        let mut names = match std::mem::take(&mut self.selected_category) {
+            SelectedCategory::All => BTreeSet::new(),
+            SelectedCategory::Uncategorized => BTreeSet::from([String::new()]),
+            SelectedCategory::Category(i) => BTreeSet::from([self.categories[i - 2].clone()]),
+            SelectedCategory::Many(names) => names,
+        };
+        if !names.remove(&name) {
+            names.insert(name);
+        }
+
+        self.selected_category = if names.is_empty() {
+            SelectedCategory::All
+        } else {
+            SelectedCategory::Many(names)
+        };
+        self.select_first_torrent();
     }
 
     pub fn choose_selected_category(&mut self) {
-        if let Some(i) = self.categories_list.state.selected() {
-            self.selected_category = match i {
-                0 => SelectedCategory::All,
-                1 => SelectedCategory::Uncategorized,
-                i => SelectedCategory::Category(i),
-            };
-            self.torrents_table.state.select(None);
+        if let Some(category) = self.get_highlighted_category() {
+            self.selected_category = category;
+            self.select_first_torrent();
         }
     }
 
@@ -350,34 +1374,296 @@ impl App {
     }
 
     pub fn reset_current_action(&mut self) {
+        // back to where the action was started
+        self.current_route = match self.current_action {
+            Some(Action::PauseCategory(_)) => Route::Categories,
+            Some(Action::RemoveCategory(_)) | Some(Action::DeleteTag(_)) => Route::Manage,
+            _ => Route::Torrents,
+        };
         self.current_action = None;
         self.confirm = false;
-        self.current_route = Route::Torrents;
     }
 
     pub async fn apply_current_action(&mut self) {
         if self.confirm {
-            if let Some(torrent) = self.get_selected_torrent() {
-                if let Some(ref action) = self.current_action {
-                    match action {
-                        Action::Delete => {
+            if let Some(ref action) = self.current_action {
+                match action {
+                    Action::Delete => {
+                        if let Some(torrent) = self.get_selected_torrent() {
                             self.api_tx
                                 .send(ApiEvent::Delete(torrent.hash.clone()))
                                 .await
                                 .unwrap();
                         }
-                        Action::DeleteFiles => {
+                    }
+                    Action::DeleteFiles => {
+                        if let Some(torrent) = self.get_selected_torrent() {
                             self.api_tx
                                 .send(ApiEvent::DeleteFiles(torrent.hash.clone()))
                                 .await
                                 .unwrap();
                         }
                     }
+                    Action::PauseCategory(category) => {
+                        let hashes = self.get_category_hashes(category);
+                        if !hashes.is_empty() {
+                            self.api_tx.send(ApiEvent::PauseMany(hashes)).await.unwrap();
+                        }
+                    }
+                    Action::RemoveCategory(category) => {
+                        self.api_tx
+                            .send(ApiEvent::RemoveCategory(category.clone()))
+                            .await
+                            .unwrap();
+                    }
+                    Action::DeleteTag(tag) => {
+                        self.api_tx
+                            .send(ApiEvent::DeleteTag(tag.clone()))
+                            .await
+                            .unwrap();
+                    }
+                    Action::JumpToDuplicate(hash) => {
+                        let hash = hash.clone();
+                        self.jump_to_torrent(&hash);
+                    }
+                    Action::ForceStartAll(hashes) => {
+                        let n = hashes.len();
+                        self.api_tx
+                            .send(ApiEvent::ForceStartMany(hashes.clone()))
+                            .await
+                            .unwrap();
+                        self.notification = Some(Notification::ForceStartedTorrents(n));
+                    }
+                    Action::CopyNames(names) => {
+                        let names = names.clone();
+                        self.copy_names(names);
+                    }
+                    Action::AddTrackersToAll(hashes, urls) => {
+                        self.api_tx
+                            .send(ApiEvent::AddTrackers(hashes.clone(), urls.clone()))
+                            .await
+                            .unwrap();
+                    }
                 }
             }
         }
 
         self.reset_current_action();
-        self.current_route = Route::Torrents;
+    }
+
+    /// One name per line
+    pub fn copy_names(&mut self, names: Vec<String>) {
+        let n = names.len();
+        self.notification = match clipboard::copy(&names.join("\n")) {
+            Ok(()) => Some(Notification::CopiedNames(n)),
+            Err(e) => Some(Notification::CommandFailed(format!("Clipboard: {e}"))),
+        };
+    }
+
+    pub fn get_category_hashes(&self, category: &SelectedCategory) -> Vec<String> {
+        self.get_category_torrents(category)
+            .into_iter()
+            .map(|t| t.hash.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn test_app() -> App {
+        let (api_tx, _) = mpsc::channel(1);
+        App::new("http://localhost:8080", api_tx, Config::default())
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn click_maps_to_rows_of_different_heights() {
+        // border at 10, header at 11 with a margin line, rows from 13
+        let rect = Rect::new(0, 10, 80, 12);
+        let heights = [1, 2, 1, 3, 1];
+
+        assert_eq!(table_row_at(10, rect, 2, &heights, 0), None);
+        assert_eq!(table_row_at(12, rect, 2, &heights, 0), None);
+        assert_eq!(table_row_at(13, rect, 2, &heights, 0), Some(0));
+        assert_eq!(table_row_at(14, rect, 2, &heights, 0), Some(1));
+        assert_eq!(table_row_at(15, rect, 2, &heights, 0), Some(1));
+        assert_eq!(table_row_at(16, rect, 2, &heights, 0), Some(2));
+        assert_eq!(table_row_at(19, rect, 2, &heights, 0), Some(3));
+        assert_eq!(table_row_at(20, rect, 2, &heights, 0), Some(4));
+        // bottom border
+        assert_eq!(table_row_at(21, rect, 2, &heights, 0), None);
+    }
+
+    #[test]
+    fn click_counts_rows_from_the_offset() {
+        let rect = Rect::new(0, 0, 80, 10);
+        let heights = [2, 2, 1, 1];
+
+        assert_eq!(table_row_at(2, rect, 1, &heights, 1), Some(1));
+        assert_eq!(table_row_at(4, rect, 1, &heights, 1), Some(2));
+        assert_eq!(table_row_at(5, rect, 1, &heights, 1), Some(3));
+        // below the last row
+        assert_eq!(table_row_at(6, rect, 1, &heights, 1), None);
+    }
+
+    #[test]
+    fn search_match_is_a_range_in_the_original_name() {
+        assert_eq!(find_search_match("Ubuntu ISO", "iso", "iso"), Some(7..10));
+        assert_eq!(
+            find_search_match("Some Movie 2023", "movie 2023", "movie.2023"),
+            Some(5..15)
+        );
+        assert_eq!(
+            find_search_match("Some.Movie.2023", "movie 2023", "movie.2023"),
+            Some(5..15)
+        );
+        assert_eq!(find_search_match("Ubuntu", "debian", "debian"), None);
+        assert_eq!(find_search_match("Ubuntu", "", ""), Some(0..0));
+    }
+
+    #[test]
+    fn search_match_survives_lowercase_changing_the_length() {
+        // "İ" is 2 bytes, lowercase is "i̇", 3 bytes
+        let name = "İstanbul Guide";
+        let range = find_search_match(name, "guide", "guide").unwrap();
+        assert_eq!(&name[range], "Guide");
+        let range = find_search_match(name, "i̇stanbul", "i̇stanbul").unwrap();
+        assert_eq!(&name[range], "İstanbul");
+    }
+
+    #[test]
+    fn categories_are_ordered_the_same_after_reload_and_sync() {
+        // reload: all categories at once, in hash map order
+        let mut reloaded = test_app();
+        reloaded.set_categories(names(&["movies", "TV", "Anime", "music", "anime"]));
+
+        // sync: new categories are appended to the known ones
+        let mut synced = test_app();
+        synced.set_categories(names(&["TV", "movies"]));
+        let mut categories = synced.categories.clone();
+        categories.extend(names(&["music", "anime", "Anime"]));
+        synced.set_categories(categories);
+
+        assert_eq!(reloaded.categories, synced.categories);
+        assert_eq!(
+            reloaded.categories,
+            names(&["Anime", "anime", "movies", "music", "TV"])
+        );
+    }
+
+    #[test]
+    fn reload_keeps_the_selected_torrent_while_searching() {
+        use crate::model::tests::torrent;
+
+        let mut app = test_app();
+        app.torrents = vec![
+            torrent("a", "ubuntu-22.04"),
+            torrent("b", "ubuntu-20.04"),
+            torrent("c", "debian-12"),
+        ];
+        app.search_input.set_value("Ubuntu");
+        app.torrents_table.state.select(app.torrent_row_index("a"));
+        assert_eq!(app.torrents_table.state.selected(), Some(1));
+
+        // the same steps as the reload, a new match sorts before the selection
+        let selected_hash = app.get_selected_torrent().map(|t| t.hash.clone());
+        app.torrents = vec![
+            torrent("a", "ubuntu-22.04"),
+            torrent("b", "ubuntu-20.04"),
+            torrent("c", "debian-12"),
+            torrent("d", "ubuntu-18.04"),
+        ];
+        app.reselect_torrent(selected_hash);
+
+        assert_eq!(app.torrents_table.state.selected(), Some(2));
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "a");
+    }
+
+    #[test]
+    fn category_groups_are_ordered_like_the_categories_list() {
+        let mut app = test_app();
+        for (hash, category) in [("a", "Zeta"), ("b", "anime"), ("c", "Anime"), ("d", "")] {
+            let mut torrent = crate::model::tests::torrent(hash, hash);
+            torrent.category = category.to_owned();
+            app.torrents.push(torrent);
+        }
+        app.set_categories(names(&["Zeta", "anime", "Anime"]));
+        app.group_by_category = true;
+
+        let headers: Vec<String> = app
+            .get_table_rows()
+            .iter()
+            .filter_map(|row| match row {
+                TableRow::Category(group) => Some(group.name.to_owned()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(headers[1..], app.categories[..]);
+        assert_eq!(headers, names(&["", "Anime", "anime", "Zeta"]));
+    }
+
+    #[test]
+    fn selection_is_never_left_on_an_added_date_header() {
+        const DAY: i64 = 24 * 60 * 60;
+        let now = unix_now();
+        let mut app = test_app();
+        for (hash, added_on) in [("a", now), ("b", now - 3 * DAY), ("c", now - 4 * DAY)] {
+            let mut torrent = crate::model::tests::torrent(hash, hash);
+            torrent.added_on = added_on;
+            app.torrents.push(torrent);
+        }
+        app.group_by_added = true;
+
+        app.select_first_torrent();
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "a");
+
+        app.torrents_table.state.select(None);
+        app.ensure_selection();
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "a");
+
+        // "c" is gone, its row is past the end now
+        app.torrents_table.state.select(Some(4));
+        app.torrents.retain(|t| t.hash != "c");
+        app.reselect_torrent(Some("c".to_owned()));
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "b");
+
+        // a header in the middle, the torrent below it is the nearest
+        app.torrents_table.state.select(Some(2));
+        app.ensure_selection();
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "b");
+    }
+
+    #[test]
+    fn added_groups_get_their_own_header_rows() {
+        const DAY: i64 = 24 * 60 * 60;
+        let now = unix_now();
+        let mut app = test_app();
+        for (hash, added_on) in [("a", now), ("b", now - 3 * DAY), ("c", now - DAY / 2)] {
+            let mut torrent = crate::model::tests::torrent(hash, hash);
+            torrent.added_on = added_on;
+            app.torrents.push(torrent);
+        }
+        app.group_by_added = true;
+
+        let rows: Vec<String> = app
+            .get_table_rows()
+            .iter()
+            .map(|row| match row {
+                TableRow::Added(group) => group.title().to_owned(),
+                TableRow::Torrent(t) => t.hash.clone(),
+                TableRow::Category(_) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            names(&["Last 24 hours", "a", "c", "Last 7 days", "b"])
+        );
     }
 }
@@ -0,0 +1,94 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{config::Config, model::TorrentInfo};
+
+/// Template is split into arguments before substitution,
+/// so values with spaces or quotes always stay a single argument
+pub fn build_command_args(
+    template: &str,
+    torrent: &TorrentInfo,
+) -> Result<Vec<String>, shell_words::ParseError> {
+    let args = shell_words::split(template)?
+        .iter()
+        .map(|arg| substitute(arg, torrent))
+        .collect();
+
+    Ok(args)
+}
+
+// single pass, so substituted values are never expanded again
+fn substitute(arg: &str, torrent: &TorrentInfo) -> String {
+    let mut result = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        match &rest[1..end] {
+            "hash" => result.push_str(&torrent.hash),
+            "name" => result.push_str(&torrent.name),
+            "content_path" => result.push_str(&torrent.content_path),
+            "save_path" => result.push_str(&torrent.save_path),
+            _ => result.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Opener from the config or the OS default, only spawn failures are reported
+pub fn open_path(config: &Config, path: &Path) -> Result<(), String> {
+    let template = match config.opener(path) {
+        Some(template) => template,
+        None => {
+            open::that_in_background(path);
+            return Ok(());
+        }
+    };
+
+    spawn_opener(template, path)
+}
+
+/// Folder opener from the config, it's run even if the path is not local,
+/// the OS default otherwise, only for local paths
+pub fn open_folder(config: &Config, path: &Path) -> Result<(), String> {
+    match config.folder_opener {
+        Some(ref template) => spawn_opener(template, path),
+        None if path.exists() => {
+            open::that_in_background(path);
+            Ok(())
+        }
+        None => Err(format!("{} is not found", path.display())),
+    }
+}
+
+fn spawn_opener(template: &str, path: &Path) -> Result<(), String> {
+    let args = shell_words::split(template).map_err(|e| format!("Opener \"{template}\": {e}"))?;
+    if args.is_empty() {
+        return Err(format!("Opener for {} is empty", path.display()));
+    }
+
+    let mut child = Command::new(&args[0])
+        .args(&args[1..])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{}: {e}", args[0]))?;
+    // reaped in the background, like open::that_in_background
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
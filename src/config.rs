@@ -0,0 +1,252 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::model::{StateFilter, StatsSegment, TorrentFile, TorrentInfo};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // name -> template, e.g. play = "mpv {content_path}"
+    // placeholders: {hash}, {name}, {content_path}, {save_path}
+    pub commands: BTreeMap<String, String>,
+    // name -> filter, e.g. movies = { category = "Movies", state = "downloading" }
+    pub filters: BTreeMap<String, SmartFilter>,
+    pub data_cap: Option<DataCap>,
+    pub speed_in_bits: bool,      // bps, Kbps, Mbps instead of B/s, K/s, M/s
+    pub stats: Vec<StatsSegment>, // empty means all in the default order
+    pub open_main_file: Option<MainFile>, // instead of the file picker
+    // category -> command name or "open", "*" for any category
+    pub on_complete: BTreeMap<String, String>,
+    pub show_category_errors: bool, // errored and missing files count in the categories list
+    pub compact: bool,              // initial compact mode, no margin under the table header
+    // file extension -> command, "*" for any file, e.g. mkv = "mpv"
+    // the path is passed as the last argument, the OS default is used otherwise
+    pub openers: BTreeMap<String, String>,
+    // command for "O" and "H" instead of the OS default, the path is the last argument
+    // and may not exist locally, e.g. "kitty ssh -t seedbox ranger"
+    pub folder_opener: Option<String>,
+    pub on_exit: Option<ExitAction>, // all torrents, not only the visible ones
+    pub on_enter: EnterAction,       // on the torrents screen, "o" always opens
+    pub inbox: Option<PathBuf>,      // directory with .torrent files to add from
+    pub inbox_after_add: InboxAfterAdd,
+    pub add_paused: bool, // initial state, toggled in the add prompt and the inbox
+    // server path prefix -> local path prefix, when qBittorrent runs elsewhere
+    // e.g. "/downloads" = "/mnt/seedbox/downloads"
+    pub path_map: BTreeMap<String, String>,
+    // category -> path_map just for it, the global one is used if none of its prefixes match
+    pub category_path_map: BTreeMap<String, BTreeMap<String, String>>,
+    pub max_torrents: usize, // rendered after filters and sort, 0 means unlimited
+    pub idle_after_minutes: u64, // no syncing after this long without input, 0 means never
+}
+
+/// What happens to an inbox file once qBittorrent accepts it
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxAfterAdd {
+    #[default]
+    Keep,
+    Delete,
+    MoveTo(PathBuf),
+}
+
+impl InboxAfterAdd {
+    pub fn apply(&self, path: &Path) -> std::io::Result<()> {
+        match self {
+            Self::Keep => Ok(()),
+            Self::Delete => fs::remove_file(path),
+            Self::MoveTo(dir) => {
+                let target = dir.join(path.file_name().unwrap_or_default());
+                // rename does not work across filesystems
+                if fs::rename(path, &target).is_err() {
+                    fs::copy(path, &target)?;
+                    fs::remove_file(path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// .torrent files directly in the directory, sorted by name
+pub fn scan_inbox(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_torrent = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("torrent"))
+            .unwrap_or(false);
+        if is_torrent && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// What Enter does on the torrents screen
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnterAction {
+    #[default]
+    Open,
+    Info,
+    Files, // the file list, nothing is opened
+    Nothing,
+}
+
+/// Run on quit, before logout
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitAction {
+    PauseAll,
+    ResumeAll,
+}
+
+/// Which file of a multi-file torrent is opened directly
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MainFile {
+    Largest,
+    FirstVideo,
+}
+
+const VIDEO_EXTENSIONS: [&str; 9] = [
+    "mkv", "mp4", "avi", "mov", "webm", "m4v", "wmv", "ts", "flv",
+];
+
+impl MainFile {
+    /// None falls back to the file picker
+    pub fn pick(self, files: &[TorrentFile]) -> Option<&TorrentFile> {
+        match self {
+            Self::Largest => files.iter().max_by_key(|f| f.size),
+            Self::FirstVideo => files.iter().find(|f| {
+                Path::new(&f.name)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| VIDEO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                    .unwrap_or(false)
+            }),
+        }
+    }
+}
+
+/// Downloaded + uploaded data this session
+#[derive(Debug, Deserialize)]
+pub struct DataCap {
+    pub limit_mb: u64,
+    #[serde(default)]
+    pub enable_alt_speed_limits: bool, // once, when the cap is exceeded
+}
+
+impl DataCap {
+    pub fn is_exceeded(&self, session_data: i64) -> bool {
+        session_data >= 0 && session_data as u64 >= self.limit_mb * 1024 * 1024
+    }
+}
+
+/// All set parts must match
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SmartFilter {
+    pub category: Option<String>, // empty string means "without category"
+    pub state: StateFilter,
+    pub name: Option<String>,
+}
+
+impl SmartFilter {
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        if let Some(ref category) = self.category {
+            if &torrent.category != category {
+                return false;
+            }
+        }
+
+        if let Some(ref name) = self.name {
+            if !torrent.name.to_lowercase().contains(&name.to_lowercase()) {
+                return false;
+            }
+        }
+
+        self.state.matches(torrent)
+    }
+}
+
+impl Config {
+    pub fn on_complete_action(&self, category: &str) -> Option<&str> {
+        self.on_complete
+            .get(category)
+            .or_else(|| self.on_complete.get("*"))
+            .map(String::as_str)
+    }
+
+    pub fn opener(&self, path: &Path) -> Option<&str> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.openers.get(&ext.to_lowercase()))
+            .or_else(|| self.openers.get("*"))
+            .map(String::as_str)
+    }
+
+    /// Mapping of the category first, then the global one, the path as is otherwise
+    pub fn rewrite_path(&self, path: &str, category: &str) -> PathBuf {
+        let path = Path::new(path);
+        self.category_path_map
+            .get(category)
+            .and_then(|path_map| map_path(path_map, path))
+            .or_else(|| map_path(&self.path_map, path))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    pub fn stats_segments(&self) -> &[StatsSegment] {
+        if self.stats.is_empty() {
+            &StatsSegment::ALL
+        } else {
+            &self.stats
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("qbtui").join("config.toml"))
+    }
+
+    /// Explicit path must exist, default path is optional
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, is_explicit) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        if !is_explicit && !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read config {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Could not parse config {}", path.display()))
+    }
+}
+
+// longest matching prefix wins, whole path components only
+fn map_path(path_map: &BTreeMap<String, String>, path: &Path) -> Option<PathBuf> {
+    path_map
+        .iter()
+        .filter_map(|(remote, local)| {
+            path.strip_prefix(remote)
+                .ok()
+                .map(|rest| (remote.len(), Path::new(local).join(rest)))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, local_path)| local_path)
+}
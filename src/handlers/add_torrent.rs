@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{Action, AddTorrentField, App, Route},
+    magnet,
+    model::AddTorrentOptions,
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            add_torrent(app).await;
+        }
+        KeyEvent {
+            code: KeyCode::Tab, ..
+        } => {
+            app.add_torrent_field = app.add_torrent_field.next();
+        }
+        KeyEvent {
+            code: KeyCode::BackTab,
+            ..
+        } => {
+            app.add_torrent_field = app.add_torrent_field.prev();
+        }
+        KeyEvent {
+            code: KeyCode::Char('p'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            app.add_paused = !app.add_paused;
+        }
+        KeyEvent {
+            code: KeyCode::Up,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } if app.add_torrent_field == AddTorrentField::Source => {
+            let len = app.add_history.entries().len();
+            let pos = match app.add_history_pos {
+                Some(pos) => std::cmp::min(pos + 1, len.saturating_sub(1)),
+                None => 0,
+            };
+            recall(app, Some(pos));
+        }
+        KeyEvent {
+            code: KeyCode::Down,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } if app.add_torrent_field == AddTorrentField::Source => {
+            let pos = match app.add_history_pos {
+                Some(0) | None => None,
+                Some(pos) => Some(pos - 1),
+            };
+            recall(app, pos);
+        }
+        _ => {
+            app.get_add_torrent_input().handle_key_event(key_event);
+        }
+    }
+}
+
+// None goes back to an empty prompt, like in a shell
+fn recall(app: &mut App, pos: Option<usize>) {
+    match pos.and_then(|pos| app.add_history.entries().get(pos).cloned()) {
+        Some(entry) => {
+            app.add_torrent_input.set_value(&entry);
+            app.add_history_pos = pos;
+        }
+        None if pos.is_none() => {
+            app.add_torrent_input.clear();
+            app.add_history_pos = None;
+        }
+        None => {}
+    }
+}
+
+async fn add_torrent(app: &mut App) {
+    let url = app.add_torrent_input.value().trim().to_owned();
+    if url.is_empty() {
+        app.add_torrent_field = AddTorrentField::Source;
+        return;
+    }
+
+    // qBittorrent silently ignores torrents that are already added
+    if let Some(hash) = magnet::info_hash(&url) {
+        if app.torrents.iter().any(|t| t.hash == hash) {
+            app.set_current_action(Action::JumpToDuplicate(hash));
+            return;
+        }
+    }
+
+    // an existing path is uploaded, read errors are reported by the api handler
+    let options = AddTorrentOptions::new(
+        app.add_paused,
+        app.add_category_input.value(),
+        app.add_save_path_input.value(),
+    );
+    let path = Path::new(&url);
+    let event = if path.exists() {
+        ApiEvent::AddFile(path.to_path_buf(), options)
+    } else {
+        ApiEvent::AddUrl(url, options)
+    };
+    app.api_tx.send(event).await.unwrap();
+    app.current_route = Route::Torrents;
+}
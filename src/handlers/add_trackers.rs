@@ -0,0 +1,55 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{Action, App, Notification, Route};
+
+const TRACKER_SCHEMES: [&str; 4] = ["http", "https", "udp", "wss"];
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            add_trackers(app);
+        }
+        _ => {
+            app.add_trackers_input.handle_key_event(key_event);
+        }
+    }
+}
+
+fn add_trackers(app: &mut App) {
+    let mut urls = Vec::new();
+    for url in app.add_trackers_input.value().split_whitespace() {
+        if !is_tracker_url(url) {
+            // the prompt stays open to fix it
+            app.notification = Some(Notification::InvalidTrackerUrl(url.to_owned()));
+            return;
+        }
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_owned());
+        }
+    }
+    if urls.is_empty() {
+        return;
+    }
+
+    // every torrent in view, asked first like the other actions on all of them
+    let hashes = std::mem::take(&mut app.add_trackers_hashes);
+    app.set_current_action(Action::AddTrackersToAll(hashes, urls));
+}
+
+fn is_tracker_url(url: &str) -> bool {
+    match reqwest::Url::parse(url) {
+        Ok(url) => TRACKER_SCHEMES.contains(&url.scheme()) && url.host_str().is_some(),
+        Err(_) => false,
+    }
+}
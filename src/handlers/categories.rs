@@ -1,8 +1,24 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::app::{App, Route};
+use crate::{
+    api::ApiEvent,
+    app::{Action, App, Route, SelectedCategory},
+};
 
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if app.is_creating_category {
+        handle_new_category_key_event(key_event, app).await;
+        return;
+    }
+    if app.editing_category.is_some() {
+        handle_edit_category_key_event(key_event, app).await;
+        return;
+    }
+    if app.is_filtering_categories {
+        handle_filter_key_event(key_event, app);
+        return;
+    }
+
     #[allow(clippy::single_match)]
     match key_event {
         KeyEvent {
@@ -16,6 +32,15 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             KeyCode::Char('j') | KeyCode::Down => {
                 next_category(app);
             }
+            KeyCode::Char('/') => {
+                app.is_filtering_categories = true;
+            }
+            KeyCode::Char('n') => {
+                app.new_category_name.clear();
+                app.new_category_save_path.clear();
+                app.is_new_category_save_path_focused = false;
+                app.is_creating_category = true;
+            }
             KeyCode::Char('k') | KeyCode::Up => {
                 prev_category(app);
             }
@@ -23,12 +48,154 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
                 app.choose_selected_category();
                 app.current_route = Route::Torrents;
             }
+            KeyCode::Char(' ') => {
+                app.toggle_highlighted_category();
+            }
+            KeyCode::Char('p') => {
+                if let Some(category) = app.get_highlighted_category() {
+                    app.set_current_action(Action::PauseCategory(category));
+                }
+            }
+            KeyCode::Char('e') => {
+                // All and Uncategorized have no save path of their own
+                if let Some(SelectedCategory::Category(i)) = app.get_highlighted_category() {
+                    let name = app.categories[i - 2].clone();
+                    let save_path = app
+                        .category_save_paths
+                        .get(&name)
+                        .cloned()
+                        .unwrap_or_default();
+                    app.category_save_path.set_value(&save_path);
+                    app.editing_category = Some(name);
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(category) = app.get_highlighted_category() {
+                    let hashes = app.get_category_hashes(&category);
+                    if !hashes.is_empty() {
+                        app.api_tx.send(ApiEvent::ResumeMany(hashes)).await.unwrap();
+                    }
+                }
+            }
             _ => {}
         },
         _ => {}
     }
 }
 
+async fn handle_new_category_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.is_creating_category = false;
+        }
+        KeyEvent {
+            code: KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down,
+            ..
+        } => {
+            app.is_new_category_save_path_focused = !app.is_new_category_save_path_focused;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            let name = app.new_category_name.value().trim().to_owned();
+            if name.is_empty() {
+                app.is_new_category_save_path_focused = false;
+                return;
+            }
+
+            // empty save path is the default one
+            let save_path = app.new_category_save_path.value().trim().to_owned();
+            app.api_tx
+                .send(ApiEvent::CreateCategory(name, save_path))
+                .await
+                .unwrap();
+            app.is_creating_category = false;
+        }
+        _ => {
+            if app.is_new_category_save_path_focused {
+                app.new_category_save_path.handle_key_event(key_event);
+            } else {
+                app.new_category_name.handle_key_event(key_event);
+            }
+        }
+    }
+}
+
+async fn handle_edit_category_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.editing_category = None;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            // the prompt stays open until there is a path
+            let save_path = app.category_save_path.value().trim().to_owned();
+            if save_path.is_empty() {
+                return;
+            }
+
+            if let Some(name) = app.editing_category.take() {
+                app.api_tx
+                    .send(ApiEvent::EditCategory(name, save_path))
+                    .await
+                    .unwrap();
+            }
+        }
+        _ => {
+            app.category_save_path.handle_key_event(key_event);
+        }
+    }
+}
+
+fn handle_filter_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.categories_filter.clear();
+            app.is_filtering_categories = false;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            // the filter stays, j/k move within the matches
+            app.is_filtering_categories = false;
+        }
+        KeyEvent {
+            code: KeyCode::Down,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => next_category(app),
+        KeyEvent {
+            code: KeyCode::Up,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => prev_category(app),
+        _ => {
+            if app.categories_filter.handle_key_event(key_event) {
+                app.apply_categories_filter();
+            }
+        }
+    }
+}
+
 pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
     if let MouseEventKind::Down(MouseButton::Left) = mouse_event.kind {
         app.left_click = (mouse_event.column, mouse_event.row);
@@ -42,10 +209,10 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
                 && app.left_click.0 <= rect_col_end
                 && app.left_click.1 >= rect_row_start
             {
-                let mut i: usize = (app.left_click.1 - rect_row_start).into();
-                i += app.categories_list.state.offset();
+                let mut pos: usize = (app.left_click.1 - rect_row_start).into();
+                pos += app.categories_view.offset();
 
-                if app.categories_list.items.len() > i {
+                if let Some(&i) = app.filtered_category_indexes().get(pos) {
                     app.categories_list.state.select(Some(i));
                     app.choose_selected_category();
                     app.current_route = Route::Torrents;
@@ -55,30 +222,89 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
     }
 }
 
+// within the filtered list
 fn next_category(app: &mut App) {
-    let i = match app.categories_list.state.selected() {
-        Some(i) => {
-            if i >= app.categories_list.items.len() - 1 {
+    let indexes = app.filtered_category_indexes();
+    let pos = match app
+        .categories_list
+        .state
+        .selected()
+        .and_then(|i| indexes.iter().position(|&j| j == i))
+    {
+        Some(pos) => {
+            if pos >= indexes.len() - 1 {
                 0
             } else {
-                i + 1
+                pos + 1
             }
         }
         None => 0,
     };
-    app.categories_list.state.select(Some(i));
+    app.categories_list.state.select(Some(indexes[pos]));
 }
 
 fn prev_category(app: &mut App) {
-    let i = match app.categories_list.state.selected() {
-        Some(i) => {
-            if i == 0 {
-                app.categories_list.items.len() - 1
+    let indexes = app.filtered_category_indexes();
+    let pos = match app
+        .categories_list
+        .state
+        .selected()
+        .and_then(|i| indexes.iter().position(|&j| j == i))
+    {
+        Some(pos) => {
+            if pos == 0 {
+                indexes.len() - 1
             } else {
-                i - 1
+                pos - 1
             }
         }
         None => 0,
     };
-    app.categories_list.state.select(Some(i));
+    app.categories_list.state.select(Some(indexes[pos]));
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::config::Config;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[tokio::test]
+    async fn save_path_is_edited_in_the_list() {
+        let (api_tx, mut api_rx) = mpsc::channel(1);
+        let mut app = App::new("http://localhost:8080", api_tx, Config::default());
+        app.current_route = Route::Categories;
+        app.set_categories(vec!["movies".to_owned()]);
+        app.category_save_paths
+            .insert("movies".to_owned(), "/data/movies".to_owned());
+        app.categories_list.state.select(Some(2));
+
+        handle_key_event(press(KeyCode::Char('e')), &mut app).await;
+        assert_eq!(app.category_save_path.value(), "/data/movies");
+
+        // an empty path is not sent, the prompt stays
+        app.category_save_path.clear();
+        handle_key_event(press(KeyCode::Enter), &mut app).await;
+        assert!(api_rx.try_recv().is_err());
+        assert_eq!(app.editing_category.as_deref(), Some("movies"));
+
+        app.category_save_path.set_value("/mnt/movies");
+        handle_key_event(press(KeyCode::Enter), &mut app).await;
+        match api_rx.try_recv() {
+            Ok(ApiEvent::EditCategory(name, save_path)) => {
+                assert_eq!(
+                    (name.as_str(), save_path.as_str()),
+                    ("movies", "/mnt/movies")
+                );
+            }
+            event => panic!("unexpected {event:?}"),
+        }
+        assert!(app.editing_category.is_none());
+        assert_eq!(app.current_route, Route::Categories);
+    }
 }
@@ -0,0 +1,93 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Notification, Route},
+    command::build_command_args,
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    #[allow(clippy::single_match)]
+    match key_event {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_command(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_command(app);
+            }
+            KeyCode::Enter => {
+                run_selected_command(app).await;
+                app.current_route = Route::Torrents;
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+fn next_command(app: &mut App) {
+    let i = match app.commands_list.state.selected() {
+        Some(i) => {
+            if i >= app.commands_list.items.len() - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    app.commands_list.state.select(Some(i));
+}
+
+fn prev_command(app: &mut App) {
+    let i = match app.commands_list.state.selected() {
+        Some(i) => {
+            if i == 0 {
+                app.commands_list.items.len() - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    app.commands_list.state.select(Some(i));
+}
+
+async fn run_selected_command(app: &mut App) {
+    let name = match app
+        .commands_list
+        .state
+        .selected()
+        .and_then(|i| app.commands_list.items.get(i))
+    {
+        Some(name) => name,
+        None => return,
+    };
+    let template = &app.config.commands[name];
+
+    if let Some(torrent) = app.get_selected_torrent() {
+        match build_command_args(template, torrent) {
+            Ok(args) if !args.is_empty() => {
+                app.api_tx.send(ApiEvent::RunCommand(args)).await.unwrap();
+            }
+            Ok(_) => {
+                app.notification = Some(Notification::CommandFailed(format!(
+                    "Command \"{name}\" is empty"
+                )));
+            }
+            Err(e) => {
+                app.notification = Some(Notification::CommandFailed(format!(
+                    "Command \"{name}\": {e}"
+                )));
+            }
+        }
+    }
+}
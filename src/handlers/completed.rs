@@ -0,0 +1,72 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{App, Route};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if let KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_completed(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_completed(app);
+            }
+            KeyCode::Enter => {
+                jump_to_completed(app);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn next_completed(app: &mut App) {
+    let len = app.recently_completed.len();
+    if len == 0 {
+        return;
+    }
+
+    let i = match app.completed_list.state.selected() {
+        Some(i) if i < len - 1 => i + 1,
+        _ => 0,
+    };
+    app.completed_list.state.select(Some(i));
+}
+
+fn prev_completed(app: &mut App) {
+    let len = app.recently_completed.len();
+    if len == 0 {
+        return;
+    }
+
+    let i = match app.completed_list.state.selected() {
+        Some(i) if i > 0 => i - 1,
+        _ => len - 1,
+    };
+    app.completed_list.state.select(Some(i));
+}
+
+// the torrent may be removed since then
+fn jump_to_completed(app: &mut App) {
+    let hash = match app
+        .completed_list
+        .state
+        .selected()
+        .and_then(|i| app.recently_completed.get(i))
+    {
+        Some(completed) => completed.hash.clone(),
+        None => return,
+    };
+
+    if app.torrents.iter().any(|t| t.hash == hash) {
+        app.current_route = Route::Torrents;
+        app.jump_to_torrent(&hash);
+    }
+}
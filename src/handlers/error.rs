@@ -1,6 +1,9 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::{api::ApiEvent, app::App};
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
 
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
     #[allow(clippy::single_match)]
@@ -14,8 +17,17 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
                 app.is_running = false;
             }
             KeyCode::Char('r') => app.api_tx.send(ApiEvent::Reload).await.unwrap(),
+            KeyCode::Enter => app.reconnect_now().await,
             _ => {}
         },
+        KeyEvent {
+            code: KeyCode::Char('L'),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        } if app.login_error.is_some() => {
+            app.is_login_password_focused = !app.login_username.is_empty();
+            app.current_route = Route::Login;
+        }
         _ => {}
     }
 }
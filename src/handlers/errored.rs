@@ -0,0 +1,132 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if app.is_editing_errored_location {
+        handle_location_key_event(key_event, app).await;
+        return;
+    }
+
+    if let KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_errored(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_errored(app);
+            }
+            KeyCode::Char('r') => {
+                if let Some(hash) = get_selected_hash(app) {
+                    app.api_tx
+                        .send(ApiEvent::RecheckAndResume(hash))
+                        .await
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(hash) = get_selected_hash(app) {
+                    app.api_tx.send(ApiEvent::Resume(hash)).await.unwrap();
+                }
+            }
+            KeyCode::Char('l') => {
+                edit_location(app);
+            }
+            KeyCode::Enter => {
+                if let Some(hash) = get_selected_hash(app) {
+                    app.current_route = Route::Torrents;
+                    app.jump_to_torrent(&hash);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn handle_location_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.is_editing_errored_location = false;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            let location = app.errored_location_input.value().trim().to_owned();
+            if location.is_empty() {
+                return;
+            }
+            if let Some(hash) = get_selected_hash(app) {
+                app.api_tx
+                    .send(ApiEvent::SetLocation(hash, location))
+                    .await
+                    .unwrap();
+            }
+            app.is_editing_errored_location = false;
+        }
+        _ => {
+            app.errored_location_input.handle_key_event(key_event);
+        }
+    }
+}
+
+/// Location prompt, prefilled with the current save path
+fn edit_location(app: &mut App) {
+    let save_path = match app.errored_list.state.selected().and_then(|i| {
+        app.get_errored_torrents()
+            .get(i)
+            .map(|t| t.save_path.clone())
+    }) {
+        Some(save_path) => save_path,
+        None => return,
+    };
+    app.errored_location_input.set_value(&save_path);
+    app.is_editing_errored_location = true;
+}
+
+fn get_selected_hash(app: &App) -> Option<String> {
+    let i = app.errored_list.state.selected()?;
+    app.get_errored_torrents().get(i).map(|t| t.hash.clone())
+}
+
+fn next_errored(app: &mut App) {
+    let len = app.get_errored_torrents().len();
+    if len == 0 {
+        return;
+    }
+
+    let i = match app.errored_list.state.selected() {
+        Some(i) if i < len - 1 => i + 1,
+        _ => 0,
+    };
+    app.errored_list.state.select(Some(i));
+}
+
+fn prev_errored(app: &mut App) {
+    let len = app.get_errored_torrents().len();
+    if len == 0 {
+        return;
+    }
+
+    let i = match app.errored_list.state.selected() {
+        Some(i) if i > 0 => i - 1,
+        _ => len - 1,
+    };
+    app.errored_list.state.select(Some(i));
+}
@@ -1,13 +1,16 @@
-use std::path::Path;
-
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::app::{App, Notification, Route};
+use crate::{
+    api::ApiEvent,
+    app::{torrent_file_path, App, Notification, Route},
+    command::open_path,
+    model::{file_rows, FileRow, Priority},
+};
 
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
     if let KeyEvent {
         code,
-        modifiers: KeyModifiers::NONE,
+        modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
         ..
     } = key_event
     {
@@ -23,7 +26,16 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
                 prev_file(app);
             }
             KeyCode::Char('o') | KeyCode::Enter => {
-                open_file(app);
+                open_file(app).await;
+            }
+            KeyCode::Char(' ') => {
+                toggle_download(app).await;
+            }
+            KeyCode::Char('+') => {
+                change_priority(app, Priority::higher).await;
+            }
+            KeyCode::Char('-') => {
+                change_priority(app, Priority::lower).await;
             }
             _ => {}
         }
@@ -58,16 +70,60 @@ fn prev_file(app: &mut App) {
     app.files_list.state.select(Some(i));
 }
 
-fn open_file(app: &mut App) {
-    if let Some(i) = app.files_list.state.selected() {
-        let file = &app.current_torrent_files.as_ref().unwrap()[i];
-        let content_path = &app.current_torrent.as_ref().unwrap().content_path;
-        let path = Path::new(content_path).parent().unwrap();
-        let path = path.join(&file.name);
-        if path.exists() {
-            open::that_in_background(path);
-        } else {
-            app.notification = Some(Notification::FileNotFound);
+// Enter on a directory is the same as Space
+async fn open_file(app: &mut App) {
+    let i = match app.files_list.state.selected() {
+        Some(i) => i,
+        None => return,
+    };
+    let files = app.current_torrent_files.as_ref().unwrap();
+    let path = match file_rows(files).get(i) {
+        Some(FileRow::File { file, .. }) => {
+            torrent_file_path(&app.config, app.current_torrent.as_ref().unwrap(), file)
+        }
+        Some(FileRow::Dir { .. }) => {
+            toggle_download(app).await;
+            return;
         }
+        None => return,
+    };
+
+    if !path.exists() {
+        app.notification = Some(Notification::FileNotFound);
+    } else if let Err(reason) = open_path(&app.config, &path) {
+        app.notification = Some(Notification::CommandFailed(reason));
     }
 }
+
+// skipped ones are downloaded, everything else is skipped
+async fn toggle_download(app: &mut App) {
+    change_priority(app, |priority| match priority {
+        Priority::DoNotDownload => Priority::Normal,
+        _ => Priority::DoNotDownload,
+    })
+    .await;
+}
+
+/// Applied to the selected file or to all files of the selected directory
+async fn change_priority(app: &mut App, change: fn(Priority) -> Priority) {
+    let (i, files, torrent) = match (
+        app.files_list.state.selected(),
+        app.current_torrent_files.as_ref(),
+        app.current_torrent.as_ref(),
+    ) {
+        (Some(i), Some(files), Some(torrent)) => (i, files, torrent),
+        _ => return,
+    };
+
+    let rows = file_rows(files);
+    let row = match rows.get(i) {
+        Some(row) => row,
+        None => return,
+    };
+    // a mixed directory is treated as downloading
+    let priority = change(row.priority().unwrap_or(Priority::Normal));
+    let ids: Vec<i32> = row.files().iter().map(|f| f.index).collect();
+
+    let event = ApiEvent::SetFilePriority(torrent.hash.clone(), ids, priority as i32);
+    app.api_tx.send(event).await.unwrap();
+}
@@ -0,0 +1,47 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{App, Route};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            go_to_row(app);
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Char(c),
+            ..
+        } if !c.is_ascii_digit() => {}
+        _ => {
+            app.goto_input.handle_key_event(key_event);
+        }
+    }
+}
+
+fn go_to_row(app: &mut App) {
+    let n: usize = match app.goto_input.value().parse() {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+
+    let len = app.get_table_rows().len();
+    if len == 0 {
+        return;
+    }
+
+    // rows are numbered from 1
+    let i = n.clamp(1, len) - 1;
+    let i = app.nearest_selectable_row(i);
+    app.torrents_table.state.select(i);
+}
@@ -13,12 +13,10 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
                 app.current_route = app.on_help_route.take().unwrap();
             }
             KeyCode::Char('j') | KeyCode::Down => {
-                app.help_state.scroll += 1;
+                app.help_state.scroll_down(1);
             }
             KeyCode::Char('k') | KeyCode::Up => {
-                if app.help_state.scroll >= 1 {
-                    app.help_state.scroll -= 1;
-                }
+                app.help_state.scroll_up(1);
             }
             _ => {}
         },
@@ -28,14 +26,10 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             ..
         } => match code {
             KeyCode::Char('J') => {
-                app.help_state.scroll += 10;
+                app.help_state.scroll_down(10);
             }
             KeyCode::Char('K') => {
-                if app.help_state.scroll >= 10 {
-                    app.help_state.scroll -= 10;
-                } else {
-                    app.help_state.scroll = 0;
-                }
+                app.help_state.scroll_up(10);
             }
             _ => {}
         },
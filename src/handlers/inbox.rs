@@ -0,0 +1,80 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if let KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.inbox_files.clear();
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_file(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_file(app);
+            }
+            KeyCode::Enter => {
+                add_file(app).await;
+            }
+            KeyCode::Char('p') => {
+                app.add_paused = !app.add_paused;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn next_file(app: &mut App) {
+    let i = match app.inbox_list.state.selected() {
+        Some(i) => {
+            if i >= app.inbox_files.len() - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    app.inbox_list.state.select(Some(i));
+}
+
+fn prev_file(app: &mut App) {
+    let i = match app.inbox_list.state.selected() {
+        Some(i) => {
+            if i == 0 {
+                app.inbox_files.len() - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    app.inbox_list.state.select(Some(i));
+}
+
+// the picker stays open for the rest of the files
+async fn add_file(app: &mut App) {
+    if let Some(i) = app.inbox_list.state.selected() {
+        let path = app.inbox_files.remove(i);
+        app.api_tx
+            .send(ApiEvent::AddTorrentFile(path, app.add_paused))
+            .await
+            .unwrap();
+
+        if app.inbox_files.is_empty() {
+            app.current_route = Route::Torrents;
+        } else if i >= app.inbox_files.len() {
+            app.inbox_list.state.select(Some(app.inbox_files.len() - 1));
+        }
+    }
+}
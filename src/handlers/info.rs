@@ -1,24 +1,49 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::app::{App, Route};
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+    model::TorrentInfoState,
+};
 
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if app.info_state.is_searching {
+        handle_search_key_event(key_event, app);
+        return;
+    }
+
     match key_event {
         KeyEvent {
             code,
             modifiers: KeyModifiers::NONE,
             ..
         } => match code {
+            KeyCode::Esc if !app.info_state.search.is_empty() => {
+                app.info_state.search.clear();
+                app.info_state.current_match = None;
+            }
             KeyCode::Char('q') | KeyCode::Esc => {
                 app.current_route = Route::Torrents;
             }
+            KeyCode::Char('/') => {
+                app.info_state.search.clear();
+                app.info_state.current_match = None;
+                app.info_state.is_searching = true;
+            }
+            KeyCode::Char('n') => {
+                app.info_state.next_match();
+            }
+            KeyCode::Char('w') => {
+                app.info_state.is_wrapped = !app.info_state.is_wrapped;
+            }
+            KeyCode::Char(' ') => {
+                toggle_pause(app).await;
+            }
             KeyCode::Char('j') | KeyCode::Down => {
-                app.info_state.scroll += 1;
+                app.info_state.scroll_down(1);
             }
             KeyCode::Char('k') | KeyCode::Up => {
-                if app.info_state.scroll >= 1 {
-                    app.info_state.scroll -= 1;
-                }
+                app.info_state.scroll_up(1);
             }
             _ => {}
         },
@@ -28,17 +53,69 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             ..
         } => match code {
             KeyCode::Char('J') => {
-                app.info_state.scroll += 10;
+                app.info_state.scroll_down(10);
             }
             KeyCode::Char('K') => {
-                if app.info_state.scroll >= 10 {
-                    app.info_state.scroll -= 10;
-                } else {
-                    app.info_state.scroll = 0;
-                }
+                app.info_state.scroll_up(10);
+            }
+            KeyCode::Char('N') => {
+                app.info_state.prev_match();
             }
             _ => {}
         },
         _ => {}
     }
 }
+
+fn handle_search_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.info_state.search.clear();
+            app.info_state.is_searching = false;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.info_state.is_searching = false;
+            app.info_state.should_jump = !app.info_state.search.is_empty();
+        }
+        _ => {
+            app.info_state.search.handle_key_event(key_event);
+        }
+    }
+}
+
+async fn toggle_pause(app: &mut App) {
+    let (hash, is_running, is_complete) = match app.get_current_torrent() {
+        Some(torrent) => (
+            torrent.hash.clone(),
+            torrent.is_running(),
+            torrent.progress >= 1.0,
+        ),
+        None => return,
+    };
+
+    let event = if is_running {
+        ApiEvent::Pause(hash.clone())
+    } else {
+        ApiEvent::Resume(hash.clone())
+    };
+    app.api_tx.send(event).await.unwrap();
+
+    // optimistic, the next sync brings the real state
+    let state = match (is_running, is_complete) {
+        (true, true) => TorrentInfoState::PausedUp,
+        (true, false) => TorrentInfoState::PausedDl,
+        (false, true) => TorrentInfoState::StalledUp,
+        (false, false) => TorrentInfoState::StalledDl,
+    };
+    if let Some(torrent) = app.torrents.iter_mut().find(|t| t.hash == hash) {
+        torrent.state = state;
+    }
+}
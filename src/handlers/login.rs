@@ -0,0 +1,51 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.login_password.clear();
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Tab | KeyCode::Up | KeyCode::Down,
+            ..
+        } => {
+            app.is_login_password_focused = !app.is_login_password_focused;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            if !app.is_login_password_focused {
+                app.is_login_password_focused = true;
+                return;
+            }
+
+            if app.login_username.is_empty() {
+                app.is_login_password_focused = false;
+                return;
+            }
+
+            app.is_reconnecting = true;
+            app.current_route = Route::Torrents;
+            app.api_tx.send(ApiEvent::Login).await.unwrap();
+        }
+        _ => {
+            if app.is_login_password_focused {
+                app.login_password.handle_key_event(key_event);
+            } else {
+                app.login_username.handle_key_event(key_event);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{Action, App, ManageFocus, ManagePrompt, Route},
+    model::split_tags,
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if app.manage_prompt.is_some() {
+        handle_prompt_key_event(key_event, app).await;
+        return;
+    }
+
+    #[allow(clippy::single_match)]
+    match key_event {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => match code {
+            KeyCode::Char('q') | KeyCode::Char('m') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_item(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_item(app);
+            }
+            KeyCode::Tab
+            | KeyCode::Char('h')
+            | KeyCode::Char('l')
+            | KeyCode::Left
+            | KeyCode::Right => {
+                app.manage_focus = match app.manage_focus {
+                    ManageFocus::Categories => ManageFocus::Tags,
+                    ManageFocus::Tags => ManageFocus::Categories,
+                };
+            }
+            KeyCode::Char('n') => {
+                app.manage_input.clear();
+                app.manage_prompt = Some(match app.manage_focus {
+                    ManageFocus::Categories => ManagePrompt::NewCategory,
+                    ManageFocus::Tags => ManagePrompt::NewTags,
+                });
+            }
+            KeyCode::Char('e') if app.manage_focus == ManageFocus::Categories => {
+                if let Some(name) = get_selected_item(app) {
+                    edit_category(app, name);
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(name) = get_selected_item(app) {
+                    app.set_current_action(match app.manage_focus {
+                        ManageFocus::Categories => Action::RemoveCategory(name),
+                        ManageFocus::Tags => Action::DeleteTag(name),
+                    });
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+async fn handle_prompt_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.manage_prompt = None;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            apply_prompt(app).await;
+        }
+        _ => {
+            app.manage_input.handle_key_event(key_event);
+        }
+    }
+}
+
+async fn apply_prompt(app: &mut App) {
+    let value = app.manage_input.value().trim().to_owned();
+    let event = match app.manage_prompt {
+        Some(ManagePrompt::NewCategory) if !value.is_empty() => {
+            ApiEvent::CreateCategory(value, String::new())
+        }
+        Some(ManagePrompt::EditCategory(ref name)) if !value.is_empty() => {
+            ApiEvent::EditCategory(name.clone(), value)
+        }
+        Some(ManagePrompt::NewTags) => {
+            let tags: Vec<String> = split_tags(&value).into_iter().map(String::from).collect();
+            if tags.is_empty() {
+                return;
+            }
+            ApiEvent::CreateTags(tags)
+        }
+        _ => return,
+    };
+
+    app.api_tx.send(event).await.unwrap();
+    app.manage_prompt = None;
+}
+
+fn get_list_len(app: &App) -> usize {
+    match app.manage_focus {
+        ManageFocus::Categories => app.categories.len(),
+        ManageFocus::Tags => app.tags.len(),
+    }
+}
+
+/// Save path prompt, prefilled with the current one
+fn edit_category(app: &mut App, name: String) {
+    let save_path = app
+        .category_save_paths
+        .get(&name)
+        .cloned()
+        .unwrap_or_default();
+    app.manage_focus = ManageFocus::Categories;
+    app.manage_categories_list
+        .state
+        .select(app.categories.iter().position(|c| *c == name));
+    app.manage_input.set_value(&save_path);
+    app.manage_prompt = Some(ManagePrompt::EditCategory(name));
+}
+
+fn get_selected_item(app: &App) -> Option<String> {
+    match app.manage_focus {
+        ManageFocus::Categories => app
+            .manage_categories_list
+            .state
+            .selected()
+            .and_then(|i| app.categories.get(i)),
+        ManageFocus::Tags => app
+            .manage_tags_list
+            .state
+            .selected()
+            .and_then(|i| app.tags.get(i)),
+    }
+    .cloned()
+}
+
+fn next_item(app: &mut App) {
+    let len = get_list_len(app);
+    if len == 0 {
+        return;
+    }
+
+    let list = match app.manage_focus {
+        ManageFocus::Categories => &mut app.manage_categories_list,
+        ManageFocus::Tags => &mut app.manage_tags_list,
+    };
+    let i = match list.state.selected() {
+        Some(i) => {
+            if i >= len - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    list.state.select(Some(i));
+}
+
+fn prev_item(app: &mut App) {
+    let len = get_list_len(app);
+    if len == 0 {
+        return;
+    }
+
+    let list = match app.manage_focus {
+        ManageFocus::Categories => &mut app.manage_categories_list,
+        ManageFocus::Tags => &mut app.manage_tags_list,
+    };
+    let i = match list.state.selected() {
+        Some(i) => {
+            if i == 0 {
+                len - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    list.state.select(Some(i));
+}
@@ -1,10 +1,25 @@
+pub mod add_torrent;
+pub mod add_trackers;
 pub mod categories;
+pub mod commands;
+pub mod completed;
 pub mod dialog;
 pub mod error;
+pub mod errored;
 pub mod files;
+pub mod goto;
 pub mod help;
+pub mod inbox;
 pub mod info;
+pub mod login;
+pub mod manage;
+pub mod new_category;
 pub mod notification;
+pub mod peers;
+pub mod preferences;
+pub mod ratio;
 pub mod search;
+pub mod set_category;
 pub mod sort;
+pub mod tags;
 pub mod torrents;
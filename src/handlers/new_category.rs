@@ -0,0 +1,49 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.current_route = Route::SetCategory;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            if create_category(app).await {
+                app.current_route = Route::Torrents;
+            }
+        }
+        _ => {
+            app.new_category_input.handle_key_event(key_event);
+        }
+    }
+}
+
+async fn create_category(app: &mut App) -> bool {
+    let name = app.new_category_input.value().trim();
+    if name.is_empty() {
+        return false;
+    }
+
+    if let Some(ref torrent) = app.current_torrent {
+        // existing one is just assigned
+        let event = if app.categories.iter().any(|c| c == name) {
+            ApiEvent::SetCategory(torrent.hash.clone(), name.to_owned())
+        } else {
+            ApiEvent::CreateAndSetCategory(torrent.hash.clone(), name.to_owned())
+        };
+        app.api_tx.send(event).await.unwrap();
+    }
+
+    true
+}
@@ -0,0 +1,62 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{App, Route};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if let KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_torrent_peers = None;
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_peer(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_peer(app);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn next_peer(app: &mut App) {
+    if app.peers_table.items.is_empty() {
+        return;
+    }
+
+    let i = match app.peers_table.state.selected() {
+        Some(i) => {
+            if i >= app.peers_table.items.len() - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    app.peers_table.state.select(Some(i));
+}
+
+fn prev_peer(app: &mut App) {
+    if app.peers_table.items.is_empty() {
+        return;
+    }
+
+    let i = match app.peers_table.state.selected() {
+        Some(i) => {
+            if i == 0 {
+                app.peers_table.items.len() - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    app.peers_table.state.select(Some(i));
+}
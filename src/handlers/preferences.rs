@@ -0,0 +1,25 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{App, Route};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if let KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                app.preferences_state.scroll_down(1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.preferences_state.scroll_up(1);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{App, Route};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    if let KeyEvent {
+        code: KeyCode::Char('q') | KeyCode::Esc,
+        modifiers: KeyModifiers::NONE,
+        ..
+    } = key_event
+    {
+        app.current_route = Route::Torrents;
+    }
+}
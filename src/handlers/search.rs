@@ -5,36 +5,24 @@ use crate::app::{App, Route};
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
     match key_event {
         KeyEvent {
-            code,
+            code: KeyCode::Esc,
             modifiers: KeyModifiers::NONE,
             ..
-        } => match code {
-            KeyCode::Esc => {
-                app.search_value = String::from("");
-                app.current_route = Route::Torrents;
-                app.select_first_torrent();
-            }
-            KeyCode::Enter => {
-                app.current_route = Route::Torrents;
-                app.select_first_torrent();
-            }
-            KeyCode::Backspace => {
-                if !app.search_value.is_empty() {
-                    app.search_value.pop();
-                }
-            }
-            KeyCode::Char(c) => {
-                app.search_value.push(c);
-            }
-            _ => {}
-        },
+        } => {
+            app.search_input.clear();
+            app.current_route = Route::Torrents;
+            app.select_first_torrent();
+        }
         KeyEvent {
-            code: KeyCode::Char(c),
-            modifiers: KeyModifiers::SHIFT,
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
             ..
         } => {
-            app.search_value.push(c);
+            app.current_route = Route::Torrents;
+            app.select_first_torrent();
+        }
+        _ => {
+            app.search_input.handle_key_event(key_event);
         }
-        _ => {}
     }
 }
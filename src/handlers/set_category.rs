@@ -0,0 +1,87 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{App, Route},
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    #[allow(clippy::single_match)]
+    match key_event {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                app.current_route = Route::Torrents;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                next_category(app);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                prev_category(app);
+            }
+            KeyCode::Enter => {
+                choose_category(app).await;
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+// the last entry is "+ New category..." and is not in the items
+fn next_category(app: &mut App) {
+    let i = match app.set_category_list.state.selected() {
+        Some(i) => {
+            if i >= app.set_category_list.items.len() {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    app.set_category_list.state.select(Some(i));
+}
+
+fn prev_category(app: &mut App) {
+    let i = match app.set_category_list.state.selected() {
+        Some(i) => {
+            if i == 0 {
+                app.set_category_list.items.len()
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    app.set_category_list.state.select(Some(i));
+}
+
+async fn choose_category(app: &mut App) {
+    let i = match app.set_category_list.state.selected() {
+        Some(i) => i,
+        None => return,
+    };
+
+    let category = match app.set_category_list.items.get(i) {
+        Some(category) => category.clone(),
+        None => {
+            app.new_category_input.clear();
+            app.current_route = Route::NewCategory;
+            return;
+        }
+    };
+
+    if let Some(ref torrent) = app.current_torrent {
+        if torrent.category != category {
+            app.api_tx
+                .send(ApiEvent::SetCategory(torrent.hash.clone(), category))
+                .await
+                .unwrap();
+        }
+    }
+    app.current_route = Route::Torrents;
+}
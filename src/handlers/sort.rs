@@ -1,6 +1,13 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-use crate::app::{next_sort_order, App, Route};
+use crate::{
+    app::{next_sort_order, App, Route},
+    model::StateGroup,
+};
+
+// sort options, then the section title, then state groups
+const SORT_OPTIONS_N: usize = 3;
+const STATE_GROUPS_TITLE: usize = SORT_OPTIONS_N;
 
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
     #[allow(clippy::single_match)]
@@ -46,7 +53,7 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
                 let mut i: usize = (app.left_click.1 - rect_row_start).into();
                 i += app.categories_list.state.offset();
 
-                if app.sort_list.items.len() > i {
+                if app.sort_list.items.len() > i && i != STATE_GROUPS_TITLE {
                     app.sort_list.state.select(Some(i));
                     handle_sort_order_change(app, i);
                 }
@@ -56,7 +63,7 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
 }
 
 fn next_sort_target(app: &mut App) {
-    let i = match app.sort_list.state.selected() {
+    let mut i = match app.sort_list.state.selected() {
         Some(i) => {
             if i >= app.sort_list.items.len() - 1 {
                 0
@@ -66,11 +73,14 @@ fn next_sort_target(app: &mut App) {
         }
         None => 0,
     };
+    if i == STATE_GROUPS_TITLE {
+        i += 1;
+    }
     app.sort_list.state.select(Some(i));
 }
 
 fn prev_sort_target(app: &mut App) {
-    let i = match app.sort_list.state.selected() {
+    let mut i = match app.sort_list.state.selected() {
         Some(i) => {
             if i == 0 {
                 app.sort_list.items.len() - 1
@@ -80,14 +90,27 @@ fn prev_sort_target(app: &mut App) {
         }
         None => 0,
     };
+    if i == STATE_GROUPS_TITLE {
+        i -= 1;
+    }
     app.sort_list.state.select(Some(i));
 }
 
 fn handle_sort_order_change(app: &mut App, i: usize) {
+    // the new order applies right away, the cursor stays on the same torrent
+    let selected_hash = app.get_selected_torrent().map(|t| t.hash.clone());
     match i {
         0 => app.category_sort_order = next_sort_order(&app.category_sort_order),
         1 => app.name_sort_order = next_sort_order(&app.name_sort_order),
         2 => app.status_sort_order = next_sort_order(&app.status_sort_order),
-        _ => unreachable!(),
+        STATE_GROUPS_TITLE => {}
+        i => {
+            let group = StateGroup::ALL[i - STATE_GROUPS_TITLE - 1];
+            if !app.hidden_state_groups.remove(&group) {
+                app.hidden_state_groups.insert(group);
+            }
+            app.select_first_torrent();
+        }
     }
+    app.reselect_torrent(selected_hash);
 }
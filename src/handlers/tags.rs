@@ -0,0 +1,99 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    api::ApiEvent,
+    app::{split_last_tag, App, Route},
+    model::split_tags,
+};
+
+pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
+    match key_event {
+        KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            apply_tags(app).await;
+            app.current_route = Route::Torrents;
+        }
+        KeyEvent {
+            code: KeyCode::Tab,
+            modifiers: KeyModifiers::NONE,
+            ..
+        } => {
+            complete_tag(app);
+        }
+        _ => {
+            if app.tags_input.handle_key_event(key_event) {
+                app.tags_completion = None;
+            }
+        }
+    }
+}
+
+fn complete_tag(app: &mut App) {
+    let suggestions: Vec<String> = app.get_tag_suggestions().into_iter().cloned().collect();
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let (head, last) = split_last_tag(app.tags_input.value());
+    let (prefix, i) = match app.tags_completion.take() {
+        Some((prefix, i)) => (prefix, (i + 1) % suggestions.len()),
+        None => (last.trim().to_lowercase(), 0),
+    };
+
+    let value = if head.is_empty() {
+        suggestions[i].clone()
+    } else {
+        format!("{head} {}", suggestions[i])
+    };
+    app.tags_input.set_value(&value);
+    app.tags_completion = Some((prefix, i));
+}
+
+async fn apply_tags(app: &mut App) {
+    let torrent = match app.current_torrent {
+        Some(ref torrent) => torrent,
+        None => return,
+    };
+
+    let mut new_tags: Vec<&str> = vec![];
+    for tag in split_tags(app.tags_input.value()) {
+        if !new_tags.contains(&tag) {
+            new_tags.push(tag);
+        }
+    }
+    let current_tags = torrent.tag_list();
+
+    let tags_to_add: Vec<String> = new_tags
+        .iter()
+        .filter(|tag| !current_tags.contains(tag))
+        .map(|tag| tag.to_string())
+        .collect();
+    let tags_to_remove: Vec<String> = current_tags
+        .iter()
+        .filter(|tag| !new_tags.contains(tag))
+        .map(|tag| tag.to_string())
+        .collect();
+
+    if tags_to_add.is_empty() && tags_to_remove.is_empty() {
+        return;
+    }
+
+    app.api_tx
+        .send(ApiEvent::SetTags(
+            torrent.hash.clone(),
+            tags_to_add,
+            tags_to_remove,
+        ))
+        .await
+        .unwrap();
+}
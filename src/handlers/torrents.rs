@@ -1,12 +1,21 @@
-use std::{path::Path, time::SystemTime};
+use std::{collections::HashMap, time::SystemTime};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
 use crate::{
     api::ApiEvent,
-    app::{Action, App, Notification, Route},
+    app::{row_height, table_row_at, Action, App, Notification, Route},
+    command::{open_folder, open_path},
+    config::{scan_inbox, EnterAction},
+    model::{StateFilter, TableRow},
 };
 
+// the lowest limit the Web UI allows, 0 would mean unlimited
+const STOPPED_UPLOAD_LIMIT: i64 = 1024;
+
+// terminals may drop large clipboard requests
+const COPY_NAMES_CONFIRM_N: usize = 100;
+
 pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
     match key_event {
         KeyEvent {
@@ -30,35 +39,50 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             KeyCode::Char('/') => {
                 app.current_route = Route::Search;
             }
+            KeyCode::Char(':') => {
+                app.goto_input.clear();
+                app.current_route = Route::GoTo;
+            }
             KeyCode::Char('c') => {
                 app.current_route = Route::Categories;
             }
             KeyCode::Char('i') => {
-                if let Some(torrent) = app.get_selected_torrent() {
-                    app.current_torrent = Some(torrent.clone());
-                    app.current_route = Route::Info;
-                }
+                show_info(app).await;
             }
-            KeyCode::Char('o') | KeyCode::Enter => {
-                if app.get_selected_torrent().is_some() {
-                    let selected_torrent = app.get_selected_torrent().unwrap().clone();
-                    app.current_torrent = Some(app.get_selected_torrent().unwrap().clone());
-                    let path = Path::new(&selected_torrent.content_path);
-                    if path.exists() {
-                        if path.is_file() {
-                            open::that_in_background(path);
-                        } else {
-                            app.api_tx
-                                .send(ApiEvent::Files(selected_torrent.hash.clone()))
-                                .await
-                                .unwrap();
-                        }
-                    } else {
-                        app.notification = Some(Notification::FileNotFound);
+            KeyCode::Enter | KeyCode::Char(' ') if app.get_selected_category_group().is_some() => {
+                let name = app.get_selected_category_group().unwrap();
+                app.toggle_category_group(name);
+            }
+            KeyCode::Char('o') => {
+                open_selected_torrent(app).await;
+            }
+            KeyCode::Enter => match app.config.on_enter {
+                EnterAction::Open => open_selected_torrent(app).await,
+                EnterAction::Info => show_info(app).await,
+                EnterAction::Files => {
+                    if let Some(torrent) = app.get_selected_torrent() {
+                        let hash = torrent.hash.clone();
+                        app.current_torrent = Some(torrent.clone());
+                        app.api_tx.send(ApiEvent::FileList(hash)).await.unwrap();
                     }
                 }
-            }
+                EnterAction::Nothing => {}
+            },
             KeyCode::Char('r') => app.api_tx.send(ApiEvent::Reload).await.unwrap(),
+            KeyCode::Char('u') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    let limit = if torrent.up_limit > 0 {
+                        0
+                    } else {
+                        STOPPED_UPLOAD_LIMIT
+                    };
+                    app.api_tx
+                        .send(ApiEvent::SetUploadLimit(torrent.hash.clone(), limit))
+                        .await
+                        .unwrap();
+                }
+            }
+            KeyCode::Char(',') => app.api_tx.send(ApiEvent::Preferences).await.unwrap(),
             KeyCode::Char(' ') => {
                 if let Some(torrent) = app.get_selected_torrent() {
                     if torrent.is_running() {
@@ -98,6 +122,35 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             KeyCode::Char('t') => {
                 app.current_route = Route::Sort;
             }
+            KeyCode::Char('f') => {
+                app.next_smart_filter();
+            }
+            KeyCode::Char('a') => {
+                app.api_tx.send(ApiEvent::ToggleSpeedLimits).await.unwrap();
+            }
+            KeyCode::Char('n') => {
+                app.open_add_torrent();
+            }
+            KeyCode::Char('g') => {
+                app.group_by_added = !app.group_by_added;
+                app.group_by_category = false;
+                app.select_first_torrent();
+            }
+            KeyCode::Char('z') => {
+                app.compact = !app.compact;
+            }
+            KeyCode::Char('#') => {
+                app.show_hash_column = !app.show_hash_column;
+            }
+            KeyCode::Char('e') => {
+                if app.get_selected_torrent().is_some() && !app.config.commands.is_empty() {
+                    app.commands_list.items = app.config.commands.keys().cloned().collect();
+                    if app.commands_list.state.selected().is_none() {
+                        app.commands_list.state.select(Some(0));
+                    }
+                    app.current_route = Route::Commands;
+                }
+            }
             _ => {}
         },
         KeyEvent {
@@ -105,20 +158,274 @@ pub async fn handle_key_event(key_event: KeyEvent, app: &mut App) {
             modifiers: KeyModifiers::SHIFT,
             ..
         } => match code {
+            KeyCode::Char('G') => {
+                app.group_by_category = !app.group_by_category;
+                app.group_by_added = false;
+                app.select_first_torrent();
+            }
             KeyCode::Char('O') => {
                 open_folder_in_default_file_manager(app);
             }
+            KeyCode::Char('Y') => {
+                let names: Vec<String> = app
+                    .get_visible_torrents()
+                    .iter()
+                    .map(|t| t.name.clone())
+                    .collect();
+                if names.len() > COPY_NAMES_CONFIRM_N {
+                    app.set_current_action(Action::CopyNames(names));
+                } else if !names.is_empty() {
+                    app.copy_names(names);
+                }
+            }
+            KeyCode::Char('H') => {
+                open_save_path(app);
+            }
+            KeyCode::Char('R') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    app.api_tx
+                        .send(ApiEvent::RecheckAndResume(torrent.hash.clone()))
+                        .await
+                        .unwrap()
+                }
+            }
+            KeyCode::Char('E') => {
+                select_next_errored(app);
+            }
+            KeyCode::Char('D') => {
+                app.current_route = Route::Ratio;
+            }
+            KeyCode::Char('V') => {
+                app.unseen_completed_n = 0;
+                if app.completed_list.state.selected().is_none() {
+                    app.completed_list.state.select(Some(0));
+                }
+                app.current_route = Route::Completed;
+            }
+            KeyCode::Char('B') => {
+                let hashes: Vec<String> = app
+                    .get_visible_torrents()
+                    .iter()
+                    .map(|t| t.hash.clone())
+                    .collect();
+                if !hashes.is_empty() {
+                    app.set_current_action(Action::ForceStartAll(hashes));
+                }
+            }
+            KeyCode::Char('N') => {
+                let hashes: Vec<String> = app
+                    .get_visible_torrents()
+                    .iter()
+                    .map(|t| t.hash.clone())
+                    .collect();
+                if !hashes.is_empty() {
+                    app.api_tx
+                        .send(ApiEvent::ReannounceMany(hashes))
+                        .await
+                        .unwrap();
+                }
+            }
+            KeyCode::Char('A') => {
+                let hashes: Vec<String> = app
+                    .get_visible_torrents()
+                    .iter()
+                    .filter(|t| !t.is_running())
+                    .map(|t| t.hash.clone())
+                    .collect();
+                app.notification = Some(Notification::ResumedTorrents(hashes.len()));
+                if !hashes.is_empty() {
+                    app.api_tx.send(ApiEvent::ResumeMany(hashes)).await.unwrap();
+                }
+            }
             KeyCode::Char('X') => {
                 if app.get_selected_torrent().is_some() {
                     app.set_current_action(Action::DeleteFiles);
                 }
             }
+            KeyCode::Char('T') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    let mut tags = torrent.tag_list().join(", ");
+                    if !tags.is_empty() {
+                        tags.push_str(", ");
+                    }
+                    app.current_torrent = Some(torrent.clone());
+                    app.tags_input.set_value(&tags);
+                    app.tags_completion = None;
+                    app.current_route = Route::Tags;
+                }
+            }
+            KeyCode::Char('C') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    let mut items = vec![String::new()];
+                    items.extend(app.categories.iter().cloned());
+                    let i = items.iter().position(|c| c == &torrent.category);
+
+                    app.current_torrent = Some(torrent.clone());
+                    app.set_category_list.items = items;
+                    app.set_category_list.state.select(i.or(Some(0)));
+                    app.current_route = Route::SetCategory;
+                }
+            }
+            KeyCode::Char('m') => {
+                app.manage_prompt = None;
+                app.current_route = Route::Manage;
+            }
+            KeyCode::Char('I') => {
+                open_inbox(app);
+            }
+            KeyCode::Char('L') => {
+                app.add_trackers_hashes = app
+                    .get_visible_torrents()
+                    .iter()
+                    .map(|t| t.hash.clone())
+                    .collect();
+                if !app.add_trackers_hashes.is_empty() {
+                    app.add_trackers_input.clear();
+                    app.current_route = Route::AddTrackers;
+                }
+            }
+            KeyCode::Char('U') => {
+                app.only_no_working_tracker = !app.only_no_working_tracker;
+                if app.only_no_working_tracker {
+                    // scanned on demand, one request per torrent,
+                    // the last result would limit the view to the torrents that failed before
+                    app.no_working_tracker = None;
+                    let hashes = app
+                        .get_visible_torrents()
+                        .iter()
+                        .map(|t| t.hash.clone())
+                        .collect();
+                    app.api_tx
+                        .send(ApiEvent::ScanTrackers(hashes))
+                        .await
+                        .unwrap();
+                }
+                app.select_first_torrent();
+            }
+            KeyCode::Char('F') => {
+                app.smart_filter = None;
+                app.state_filter = StateFilter::All;
+                app.only_no_working_tracker = false;
+                app.hidden_state_groups.clear();
+                app.select_first_torrent();
+            }
+            KeyCode::Char('M') => {
+                app.hide_fetching_metadata = !app.hide_fetching_metadata;
+                app.select_first_torrent();
+            }
+            KeyCode::Char('S') => {
+                app.show_summary = !app.show_summary;
+            }
+            KeyCode::Char('Z') => {
+                if let Some(i) = app.next_row_without_peers() {
+                    app.torrents_table.state.select(Some(i));
+                }
+            }
+            KeyCode::Char('w') => {
+                app.peer_counts_mode = app.peer_counts_mode.next();
+            }
+            KeyCode::Char('b') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    let category = torrent.category.clone();
+                    let i = app.first_row_of_category(&category);
+                    app.torrents_table.state.select(i);
+                }
+            }
+            KeyCode::Char('P') => {
+                if let Some(torrent) = app.get_selected_torrent() {
+                    let hash = torrent.hash.clone();
+                    app.current_torrent = Some(torrent.clone());
+                    app.current_torrent_peers = Some(HashMap::new());
+                    app.peers_table.state.select(None);
+                    app.current_route = Route::Peers;
+                    app.api_tx.send(ApiEvent::Peers(hash)).await.unwrap();
+                }
+            }
             _ => {}
         },
+        KeyEvent {
+            code: KeyCode::Char('l'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            // the view is recomputed on every draw, only the screen is repainted
+            app.should_clear_screen = true;
+        }
+        KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            if let Some(torrent) = app.get_selected_torrent() {
+                app.api_tx
+                    .send(ApiEvent::Recheck(torrent.hash.clone()))
+                    .await
+                    .unwrap()
+            }
+        }
+        KeyEvent {
+            code: KeyCode::Char('a'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            if let Some(torrent) = app.get_selected_torrent() {
+                app.api_tx
+                    .send(ApiEvent::Reannounce(torrent.hash.clone()))
+                    .await
+                    .unwrap()
+            }
+        }
+        KeyEvent {
+            code: KeyCode::Char('e'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        } => {
+            app.is_editing_errored_location = false;
+            app.check_errored_data();
+            if app.errored_list.state.selected().is_none() {
+                app.errored_list.state.select(Some(0));
+            }
+            app.current_route = Route::Errored;
+        }
         _ => {}
     }
 }
 
+async fn show_info(app: &mut App) {
+    if let Some(torrent) = app.get_selected_torrent() {
+        let hash = torrent.hash.clone();
+        app.current_torrent = Some(torrent.clone());
+        app.current_torrent_properties = None;
+        app.current_torrent_trackers = None;
+        app.current_route = Route::Info;
+        app.api_tx.send(ApiEvent::Properties(hash)).await.unwrap();
+    }
+}
+
+async fn open_selected_torrent(app: &mut App) {
+    if app.get_selected_torrent().is_some() {
+        let selected_torrent = app.get_selected_torrent().unwrap().clone();
+        app.current_torrent = Some(app.get_selected_torrent().unwrap().clone());
+        let path = app
+            .config
+            .rewrite_path(&selected_torrent.content_path, &selected_torrent.category);
+        if path.exists() {
+            if path.is_file() {
+                if let Err(reason) = open_path(&app.config, &path) {
+                    app.notification = Some(Notification::CommandFailed(reason));
+                }
+            } else {
+                app.api_tx
+                    .send(ApiEvent::Files(selected_torrent.hash.clone()))
+                    .await
+                    .unwrap();
+            }
+        } else {
+            app.notification = Some(Notification::FileNotFound);
+        }
+    }
+}
+
 pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
     if let MouseEventKind::Down(MouseButton::Left) = mouse_event.kind {
         let elapsed_ms = app.left_click_ts.elapsed().unwrap().as_millis();
@@ -126,20 +433,44 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
         app.left_click = (mouse_event.column, mouse_event.row);
         app.left_click_ts = SystemTime::now();
 
+        if let Some(rect) = app.speed_limits_rect {
+            let (col, row) = app.left_click;
+            if col >= rect.x && col < rect.x + rect.width && row == rect.y {
+                app.api_tx.send(ApiEvent::ToggleSpeedLimits).await.unwrap();
+                return;
+            }
+        }
+
         if let Some(rect) = app.torrents_table_rect {
-            // HARDCODE
-            let rect_row_start = rect.y + 3;
             let rect_col_start = rect.x + 1;
             let rect_col_end = rect.x + rect.width;
+            let row_heights: Vec<u16> = app
+                .torrents_table
+                .items
+                .iter()
+                .map(|item| row_height(item))
+                .collect();
+            let clicked_row = table_row_at(
+                app.left_click.1,
+                rect,
+                app.torrents_header_height(),
+                &row_heights,
+                app.torrents_table.state.offset(),
+            )
+            .filter(|&i| {
+                app.get_table_rows()
+                    .get(i)
+                    .is_some_and(TableRow::is_selectable)
+            });
             if app.left_click.0 >= rect_col_start
                 && app.left_click.0 <= rect_col_end
-                && app.left_click.1 >= rect_row_start
+                && clicked_row.is_some()
             {
-                let mut i: usize = (app.left_click.1 - rect_row_start).into();
-                i += app.torrents_table.state.offset();
+                app.torrents_table.state.select(clicked_row);
 
-                if app.torrents_table.items.len() > i {
-                    app.torrents_table.state.select(Some(i));
+                if let Some(name) = app.get_selected_category_group() {
+                    app.toggle_category_group(name);
+                    return;
                 }
 
                 // double click
@@ -150,10 +481,15 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
                     let selected_torrent = app.get_selected_torrent().unwrap();
                     let current_torrent = app.current_torrent.as_ref().unwrap();
                     if selected_torrent.hash == current_torrent.hash {
-                        let path = Path::new(&selected_torrent.content_path);
+                        let path = app.config.rewrite_path(
+                            &selected_torrent.content_path,
+                            &selected_torrent.category,
+                        );
                         if path.exists() {
                             if path.is_file() {
-                                open::that_in_background(path);
+                                if let Err(reason) = open_path(&app.config, &path) {
+                                    app.notification = Some(Notification::CommandFailed(reason));
+                                }
                             } else {
                                 app.api_tx
                                     .send(ApiEvent::Files(selected_torrent.hash.clone()))
@@ -174,43 +510,142 @@ pub async fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
     }
 }
 
+/// Wraps around, added date headers are stepped over
 fn next_torrent(app: &mut App) {
-    let i = match app.torrents_table.state.selected() {
-        Some(i) => {
-            if i >= app.torrents_table.items.len() - 1 {
-                0
-            } else {
-                i + 1
-            }
-        }
-        None => 0,
-    };
-    app.torrents_table.state.select(Some(i));
+    let rows = app.get_table_rows();
+    let len = rows.len();
+    let start = app.torrents_table.state.selected().map_or(0, |i| i + 1);
+    let next = (0..len)
+        .map(|offset| (start + offset) % len)
+        .find(|&i| rows[i].is_selectable());
+    app.torrents_table.state.select(next);
 }
 
+/// Same as `next_torrent`, upwards
 fn prev_torrent(app: &mut App) {
-    let i = match app.torrents_table.state.selected() {
-        Some(i) => {
-            if i == 0 {
-                app.torrents_table.items.len() - 1
-            } else {
-                i - 1
-            }
+    let rows = app.get_table_rows();
+    let len = rows.len();
+    let prev = match app.torrents_table.state.selected() {
+        Some(i) => (1..=len)
+            .map(|offset| (i + len - offset) % len)
+            .find(|&i| rows[i].is_selectable()),
+        None => rows.iter().position(TableRow::is_selectable),
+    };
+    app.torrents_table.state.select(prev);
+}
+
+fn open_inbox(app: &mut App) {
+    let dir = match app.config.inbox {
+        Some(ref dir) => dir,
+        None => {
+            app.notification = Some(Notification::InboxFailed(
+                "Set inbox in the config to add .torrent files from it".to_owned(),
+            ));
+            return;
         }
-        None => 0,
     };
-    app.torrents_table.state.select(Some(i));
+
+    match scan_inbox(dir) {
+        Ok(files) if files.is_empty() => {
+            app.notification = Some(Notification::EmptyInbox);
+        }
+        Ok(files) => {
+            app.inbox_files = files;
+            app.inbox_list.state.select(Some(0));
+            app.current_route = Route::Inbox;
+        }
+        Err(e) => {
+            app.notification = Some(Notification::InboxFailed(format!("{}: {e}", dir.display())));
+        }
+    }
 }
 
+/// Wraps around, the selected torrent itself is checked last
+fn select_next_errored(app: &mut App) {
+    let rows = app.get_table_rows();
+    let len = rows.len();
+    let start = app.torrents_table.state.selected().map_or(0, |i| i + 1);
+    let next = (0..len)
+        .map(|offset| (start + offset) % len)
+        .find(|&i| matches!(rows[i], TableRow::Torrent(t) if t.state.is_errored()));
+
+    match next {
+        Some(i) => app.torrents_table.state.select(Some(i)),
+        None => app.notification = Some(Notification::NoErroredTorrents),
+    }
+}
+
+// a path that is not local is passed as is to folder_opener
 fn open_folder_in_default_file_manager(app: &mut App) {
     if let Some(torrent) = app.get_selected_torrent() {
-        let path = Path::new(&torrent.content_path);
-        if path.is_dir() && path.exists() {
-            open::that_in_background(path);
+        let path = app
+            .config
+            .rewrite_path(&torrent.content_path, &torrent.category);
+        let folder = if path.is_dir() && path.exists() {
+            path.as_path()
         } else if path.parent().unwrap().exists() {
-            open::that_in_background(path.parent().unwrap());
+            path.parent().unwrap()
+        } else if app.config.folder_opener.is_some() {
+            path.as_path()
         } else {
             app.notification = Some(Notification::FileNotFound);
+            return;
+        };
+        if let Err(reason) = open_folder(&app.config, folder) {
+            app.notification = Some(Notification::CommandFailed(reason));
+        }
+    }
+}
+
+// the root of the torrent's data, content_path is inside it for torrents with a subfolder
+fn open_save_path(app: &mut App) {
+    if let Some(torrent) = app.get_selected_torrent() {
+        let path = app
+            .config
+            .rewrite_path(&torrent.save_path, &torrent.category);
+        if !path.is_dir() && app.config.folder_opener.is_none() {
+            app.notification = Some(Notification::FileNotFound);
+        } else if let Err(reason) = open_folder(&app.config, &path) {
+            app.notification = Some(Notification::CommandFailed(reason));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{app::unix_now, config::Config, model::tests::torrent};
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[tokio::test]
+    async fn navigation_skips_added_date_headers() {
+        const DAY: i64 = 24 * 60 * 60;
+        let (api_tx, _api_rx) = mpsc::channel(1);
+        let mut app = App::new("http://localhost:8080", api_tx, Config::default());
+        let now = unix_now();
+        for (hash, added_on) in [("a", now), ("b", now - 3 * DAY), ("c", now - 30 * DAY)] {
+            let mut t = torrent(hash, hash);
+            t.added_on = added_on;
+            app.torrents.push(t);
+        }
+
+        handle_key_event(press(KeyCode::Char('g')), &mut app).await;
+        assert_eq!(app.get_selected_torrent().unwrap().hash, "a");
+
+        // down through every torrent and around, then back up
+        let mut hashes = vec![];
+        for code in [KeyCode::Char('j'); 4]
+            .into_iter()
+            .chain([KeyCode::Char('k'); 4])
+        {
+            handle_key_event(press(code), &mut app).await;
+            hashes.push(app.get_selected_torrent().unwrap().hash.clone());
         }
+        assert_eq!(hashes, ["b", "c", "a", "b", "a", "c", "b", "a"]);
     }
 }
@@ -0,0 +1,59 @@
+use std::{fs, path::PathBuf};
+
+const MAX_ENTRIES: usize = 50;
+
+/// Recently added magnets and URLs, newest first, kept between sessions
+#[derive(Debug, Default)]
+pub struct AddHistory {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl AddHistory {
+    /// Next to other app data, not the config, it's rewritten on every add
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_local_dir().map(|dir| dir.join("qbtui").join("add_history"))
+    }
+
+    /// Missing or unreadable file is an empty history
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| {
+                text.lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .take(MAX_ENTRIES)
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { entries, path }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Moves an entry that is already there to the front
+    pub fn push(&mut self, entry: &str) {
+        self.entries.retain(|e| e != entry);
+        self.entries.insert(0, entry.to_owned());
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+        fs::write(path, text)
+    }
+}
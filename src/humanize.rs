@@ -1,40 +1,127 @@
 const SUFFIX: [&str; 9] = ["B", "K", "M", "G", "TB", "PB", "EB", "ZB", "YB"];
+const UNKNOWN_SYMBOL: &str = "-";
 
 pub fn humanize_bytes<T: Into<f64>>(size: T) -> String {
     let size = size.into();
 
+    if !size.is_finite() {
+        return UNKNOWN_SYMBOL.to_owned();
+    }
+
+    // negative values are not expected, but should not break the layout
     if size <= 0.0 {
         return "0 B".to_string();
     }
 
     let base = size.log10() / 1024_f64.log10();
+    // anything above YB stays in YB, below 1 B stays in B
+    let exp = base.floor().clamp(0.0, (SUFFIX.len() - 1) as f64);
+
+    let mut result = format!("{:.1}", 1024_f64.powf(base - exp))
+        .trim_end_matches(".0")
+        .to_owned();
+
+    result.push(' ');
+    result.push_str(SUFFIX[exp as usize]);
+
+    result
+}
+
+const BIT_SUFFIX: [&str; 9] = [
+    "bps", "Kbps", "Mbps", "Gbps", "Tbps", "Pbps", "Ebps", "Zbps", "Ybps",
+];
+
+// network speeds are decimal
+fn humanize_bits(bits: f64) -> String {
+    if !bits.is_finite() {
+        return UNKNOWN_SYMBOL.to_owned();
+    }
+
+    if bits <= 0.0 {
+        return "0 bps".to_string();
+    }
+
+    let base = bits.log10() / 1000_f64.log10();
+    let exp = base.floor().clamp(0.0, (BIT_SUFFIX.len() - 1) as f64);
 
-    let mut result = format!("{:.1}", 1024_f64.powf(base - base.floor()))
+    let mut result = format!("{:.1}", 1000_f64.powf(base - exp))
         .trim_end_matches(".0")
         .to_owned();
 
     result.push(' ');
-    result.push_str(SUFFIX[base.floor() as usize]);
+    result.push_str(BIT_SUFFIX[exp as usize]);
 
     result
 }
 
+/// Speed is in bytes per second, sizes are always shown in bytes
+pub fn humanize_speed<T: Into<f64>>(bytes_per_sec: T, in_bits: bool) -> String {
+    let bytes_per_sec = bytes_per_sec.into();
+    if in_bits {
+        humanize_bits(bytes_per_sec * 8.0)
+    } else {
+        humanize_bytes(bytes_per_sec) + "/s"
+    }
+}
+
+/// 0 and -1 mean no limit
+pub fn humanize_up_limit(bytes_per_sec: i64, in_bits: bool) -> String {
+    if bytes_per_sec > 0 {
+        humanize_speed(bytes_per_sec as f64, in_bits)
+    } else {
+        INFINITY_SYMBOL.to_owned()
+    }
+}
+
+/// Progress is clamped to 0..=1, qBittorrent can report 1.0000001,
+/// and 100% is shown only when it's done
 pub fn humanize_percentage(v: f64) -> String {
-    format!("{:.1}%", 100.0 * v)
+    if v.is_nan() {
+        return UNKNOWN_SYMBOL.to_owned();
+    }
+    let mut percentage = 100.0 * v.clamp(0.0, 1.0);
+    if v < 1.0 {
+        percentage = percentage.min(99.9);
+    }
+    format!("{percentage:.1}%")
+}
+
+// qBittorrent/src/base/bittorrent/torrent.h - MAX_RATIO
+const MAX_RATIO: f64 = 9999.0;
+
+pub fn humanize_ratio(v: f64) -> String {
+    if v.is_nan() || v < 0.0 {
+        return UNKNOWN_SYMBOL.to_owned();
+    }
+    if v >= MAX_RATIO {
+        return INFINITY_SYMBOL.to_owned();
+    }
+    format!("{v:.2}")
 }
 
 const INFINITY: i64 = 8640000;
 const INFINITY_SYMBOL: &str = "∞";
 
-// qBittorrent/src/base/utils/misc.cpp - userFriendlyDuration
+/// qBittorrent reports unknown eta as 8640000 (100 days), negative is unknown too
+pub fn is_infinite_eta(v: i64) -> bool {
+    !(0..INFINITY).contains(&v)
+}
+
 pub fn humanize_eta(v: i64) -> String {
+    if is_infinite_eta(v) {
+        return INFINITY_SYMBOL.to_owned();
+    }
+    humanize_duration(v)
+}
+
+// qBittorrent/src/base/utils/misc.cpp - userFriendlyDuration
+pub fn humanize_duration(v: i64) -> String {
     let mut minutes = v / 60;
     let mut hours = minutes / 60;
     let mut days = hours / 24;
     let years = days / 365;
     match v {
         v if v < 0 => INFINITY_SYMBOL.to_owned(),
-        v if v >= INFINITY => INFINITY_SYMBOL.to_owned(),
         v if v == 0 => "0s".to_owned(),
         v if v < 60 => "< 1m".to_owned(),
         _ if minutes < 60 => format!("{}m", minutes),
@@ -53,3 +140,86 @@ pub fn humanize_eta(v: i64) -> String {
         _ => INFINITY_SYMBOL.to_owned(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_that_are_not_sizes_are_safe() {
+        assert_eq!(humanize_bytes(f64::NAN), "-");
+        assert_eq!(humanize_bytes(f64::INFINITY), "-");
+        assert_eq!(humanize_bytes(f64::NEG_INFINITY), "-");
+        assert_eq!(humanize_bytes(-1024.0), "0 B");
+        assert_eq!(humanize_bytes(0.0), "0 B");
+        assert_eq!(humanize_speed(-1.0, false), "0 B/s");
+    }
+
+    #[test]
+    fn bytes_stay_within_suffixes() {
+        assert_eq!(humanize_bytes(0.5), "0.5 B");
+        assert_eq!(humanize_bytes(1536.0), "1.5 K");
+        assert_eq!(humanize_bytes(1024_f64.powi(9)), "1024 YB");
+    }
+
+    #[test]
+    fn speed_in_bytes() {
+        assert_eq!(humanize_speed(0.0, false), "0 B/s");
+        assert_eq!(humanize_speed(1536.0, false), "1.5 K/s");
+        assert_eq!(humanize_speed(1024.0 * 1024.0, false), "1 M/s");
+    }
+
+    #[test]
+    fn speed_in_bits_is_decimal() {
+        assert_eq!(humanize_speed(0.0, true), "0 bps");
+        assert_eq!(humanize_speed(1.0, true), "8 bps");
+        assert_eq!(humanize_speed(125_000.0, true), "1 Mbps");
+        assert_eq!(humanize_speed(1536.0, true), "12.3 Kbps");
+    }
+
+    #[test]
+    fn percentage_is_capped_below_done() {
+        assert_eq!(humanize_percentage(1.0000001), "100.0%");
+        assert_eq!(humanize_percentage(1.0), "100.0%");
+        assert_eq!(humanize_percentage(0.9999), "99.9%");
+        assert_eq!(humanize_percentage(0.5), "50.0%");
+        assert_eq!(humanize_percentage(-0.1), "0.0%");
+        assert_eq!(humanize_percentage(f64::NAN), "-");
+    }
+
+    #[test]
+    fn ratio_bounds() {
+        assert_eq!(humanize_ratio(f64::NAN), "-");
+        assert_eq!(humanize_ratio(-1.0), "-");
+        assert_eq!(humanize_ratio(0.0), "0.00");
+        assert_eq!(humanize_ratio(9998.994), "9998.99");
+        assert_eq!(humanize_ratio(9999.0), "∞");
+        assert_eq!(humanize_ratio(f64::INFINITY), "∞");
+    }
+
+    #[test]
+    fn up_limit_follows_the_speed_mode() {
+        assert_eq!(humanize_up_limit(-1, false), "∞");
+        assert_eq!(humanize_up_limit(0, true), "∞");
+        assert_eq!(humanize_up_limit(125_000, true), "1 Mbps");
+        assert_eq!(
+            humanize_up_limit(125_000, false),
+            humanize_speed(125_000.0, false)
+        );
+    }
+
+    #[test]
+    fn eta_sentinel_is_infinite() {
+        assert!(is_infinite_eta(8640000));
+        assert!(is_infinite_eta(i64::MAX));
+        assert!(is_infinite_eta(-1));
+        assert_eq!(humanize_eta(8640000), "∞");
+    }
+
+    #[test]
+    fn eta_just_below_sentinel_is_real() {
+        assert!(!is_infinite_eta(0));
+        assert!(!is_infinite_eta(8639999));
+        assert_eq!(humanize_eta(8639999), "99d 23h");
+    }
+}
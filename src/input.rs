@@ -0,0 +1,129 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Single line text prompt with a cursor
+#[derive(Debug, Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize, // in chars
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Every char shown as *, for passwords
+    pub fn masked(&self) -> Self {
+        Self {
+            value: "*".repeat(self.value.chars().count()),
+            cursor: self.cursor,
+        }
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_owned();
+        self.cursor = self.value.chars().count();
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index(self.cursor);
+        self.value.insert(i, c);
+        self.cursor += 1;
+    }
+
+    /// Line breaks and other control chars are dropped
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            self.insert(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let i = self.byte_index(self.cursor);
+            self.value.remove(i);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            let i = self.byte_index(self.cursor);
+            self.value.remove(i);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = std::cmp::min(self.cursor + 1, self.value.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.chars().count();
+    }
+
+    /// Returns false if the event is not an editing key
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        match key_event {
+            KeyEvent {
+                code,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => match code {
+                KeyCode::Backspace => self.backspace(),
+                KeyCode::Delete => self.delete(),
+                KeyCode::Left => self.move_left(),
+                KeyCode::Right => self.move_right(),
+                KeyCode::Home => self.move_home(),
+                KeyCode::End => self.move_end(),
+                KeyCode::Char(c) => self.insert(c),
+                _ => return false,
+            },
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            } => self.insert(c),
+            KeyEvent {
+                code,
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => match code {
+                KeyCode::Char('a') => self.move_home(),
+                KeyCode::Char('e') => self.move_end(),
+                _ => return false,
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
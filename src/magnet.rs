@@ -0,0 +1,45 @@
+/// Lowercase hex v1 info hash from "xt=urn:btih:", base32 hashes are converted
+pub fn info_hash(uri: &str) -> Option<String> {
+    let query = uri
+        .get(..8)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("magnet:?"))
+        .map(|_| &uri[8..])?;
+
+    query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .filter(|(key, _)| *key == "xt")
+        .filter_map(|(_, value)| {
+            value
+                .get(..9)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("urn:btih:"))
+                .map(|_| &value[9..])
+        })
+        .find_map(|hash| match hash.len() {
+            40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(hash.to_lowercase()),
+            32 => base32_to_hex(hash),
+            _ => None,
+        })
+}
+
+// RFC 4648 alphabet, 32 chars are exactly 20 bytes
+fn base32_to_hex(hash: &str) -> Option<String> {
+    let mut hex = String::with_capacity(40);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for c in hash.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            hex.push_str(&format!("{:02x}", (buffer >> bits) & 0xff));
+        }
+    }
+
+    Some(hex)
+}
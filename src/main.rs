@@ -1,30 +1,45 @@
 use std::process::exit;
-use std::{io, sync::Arc};
+use std::{io, path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::Result;
-use api::{ApiError, ApiEvent, ApiHandler, LoginError};
+use api::{ApiError, ApiEvent, ApiHandler, ExternalError, LoginError};
 use clap::Parser;
 use tokio::sync::mpsc::channel;
 use tokio::sync::Mutex;
 use tracing_subscriber::EnvFilter;
 
 use crate::{
-    app::App,
+    app::{App, Notification},
+    config::{Config, ExitAction},
+    model::{GetTorrentListParams, StateFilter},
     ui::{start_ui, UiEvent},
 };
 
 mod api;
 mod app;
+mod clipboard;
+mod command;
+mod config;
 mod handlers;
+mod history;
 mod humanize;
+mod input;
+mod magnet;
 mod model;
+mod snapshot;
 mod ui;
 
+// including the wait for the request in flight
+const LOGOUT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// pausing or resuming everything can take a while on a busy server
+const ON_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
-    /// Format: "http://<host>:<port>"
-    #[arg(long)]
+    /// Format: "http://<host>:<port>", "http://" is assumed if the scheme is missing
+    #[arg(long, value_parser = parse_url)]
     url: String,
 
     #[arg(long)]
@@ -36,6 +51,61 @@ struct Args {
     /// Necessary if the certificate is untrusted (e.g. self-signed)
     #[arg(long)]
     do_not_verify_webui_certificate: bool,
+
+    /// Default: "<config dir>/qbtui/config.toml"
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the torrent list and exit
+    #[arg(long)]
+    once: bool,
+
+    /// Initial category, empty means "without category"
+    #[arg(long)]
+    category: Option<String>,
+
+    /// With --once, empty means "without tag"
+    #[arg(long, requires = "once")]
+    tag: Option<String>,
+
+    /// Initial state filter: all, downloading, seeding, completed, paused, resumed, active, inactive, stalled, stalled_uploading, stalled_downloading, errored
+    #[arg(long)]
+    filter: Option<StateFilter>,
+
+    /// With --once, print JSON instead of a table
+    #[arg(long, requires = "once")]
+    json: bool,
+
+    /// With --json, comma separated qBittorrent field names, e.g. "hash,name,state"
+    #[arg(long, requires = "json", value_delimiter = ',')]
+    fields: Vec<String>,
+}
+
+/// Normalizes the base url, so paths can be appended to it as is
+fn parse_url(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    let s = if s.contains("://") {
+        s.to_owned()
+    } else {
+        format!("http://{s}")
+    };
+
+    let url =
+        reqwest::Url::parse(&s).map_err(|e| format!("{e}, expected \"http://<host>:<port>\""))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!(
+            "unsupported scheme \"{}\", expected http or https",
+            url.scheme()
+        ));
+    }
+    if url.host_str().unwrap_or_default().is_empty() {
+        return Err("missing host".to_owned());
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err("query and fragment are not allowed".to_owned());
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_owned())
 }
 
 #[tokio::main]
@@ -47,15 +117,20 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
-    if !args.url.starts_with("http://") && !args.url.starts_with("https://") {
-        eprintln!("Url format: \"http://<host>:<port>\"");
-        exit(1);
-    }
+    let config = match Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e:#}");
+            exit(1);
+        }
+    };
+
+    let speed_in_bits = config.speed_in_bits;
 
     let (ui_tx, ui_rx) = channel::<UiEvent>(32);
     let (api_tx, mut api_rx) = channel::<ApiEvent>(32);
 
-    let app = Arc::new(Mutex::new(App::new(&args.url, api_tx.clone())));
+    let app = Arc::new(Mutex::new(App::new(&args.url, api_tx.clone(), config)));
 
     let mut api_handler = ApiHandler::new(
         Arc::clone(&app),
@@ -91,8 +166,49 @@ async fn main() -> Result<()> {
         }
     }
 
+    if args.once {
+        let mut params = GetTorrentListParams::default();
+        if let Some(filter) = args.filter {
+            params = params.with_filter(filter);
+        }
+        if let Some(ref category) = args.category {
+            params = params.with_category(category);
+        }
+        if let Some(ref tag) = args.tag {
+            params = params.with_tag(tag);
+        }
+
+        match api_handler.api.torrents_info(Some(params)).await {
+            Ok(mut torrents) => {
+                torrents.sort_by_cached_key(|t| (t.name.to_lowercase(), t.name.clone()));
+                if args.json {
+                    match snapshot::format_json(&torrents, &args.fields) {
+                        Ok(text) => println!("{text}"),
+                        Err(e) => {
+                            eprintln!("Could not serialize torrents: {e}");
+                            exit(1);
+                        }
+                    }
+                } else {
+                    println!("{}", snapshot::format_table(&torrents, speed_in_bits));
+                }
+            }
+            Err(e) => {
+                eprintln!("Could not get torrents from {}: {e}", &args.url);
+                exit(1);
+            }
+        }
+
+        let _ = api_handler.api.logout().await;
+        return Ok(());
+    }
+
     if let Err(e) = api_handler.reload().await {
         match e {
+            ApiError::External(ExternalError::WrongUrl(_)) => {
+                eprintln!("Could not connect to {}: {e}. {}", &args.url, e.hint());
+                exit(1);
+            }
             ApiError::External(_) => {
                 eprintln!("Could not connect to {}: Check connection!", &args.url);
                 exit(1);
@@ -108,6 +224,19 @@ async fn main() -> Result<()> {
         }
     }
 
+    {
+        let mut app = app.lock().await;
+        if let Some(filter) = args.filter {
+            app.state_filter = filter;
+        }
+        if let Some(ref category) = args.category {
+            if !app.select_category_by_name(category) {
+                tracing::warn!(category, "Unknown category");
+                app.notification = Some(Notification::UnknownCategory(category.clone()));
+            }
+        }
+    }
+
     let api_handler_arc1 = Arc::new(Mutex::new(api_handler));
     let api_handler_arc2 = Arc::clone(&api_handler_arc1);
 
@@ -122,8 +251,63 @@ async fn main() -> Result<()> {
 
     start_ui(Arc::clone(&app), ui_rx).await?;
 
-    let mut api_handler = api_handler_arc1.lock().await;
-    let _ = api_handler.api.logout().await;
+    let on_exit = app.lock().await.config.on_exit;
+    if let Some(action) = on_exit {
+        let result = tokio::time::timeout(ON_EXIT_TIMEOUT, async {
+            let api_handler = api_handler_arc1.lock().await;
+            match action {
+                ExitAction::PauseAll => api_handler.api.pause_all().await,
+                ExitAction::ResumeAll => api_handler.api.resume_all().await,
+            }
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Could not run {action:?} on exit: {e}"),
+            Err(_) => eprintln!("Could not run {action:?} on exit: timed out"),
+        }
+    }
+
+    // the terminal is restored by now, a pending request or a dead
+    // connection must not hold the exit for long
+    let _ = tokio::time::timeout(LOGOUT_TIMEOUT, async {
+        let mut api_handler = api_handler_arc1.lock().await;
+        api_handler.api.logout().await
+    })
+    .await;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_without_host_is_rejected() {
+        assert!(parse_url("http://").is_err());
+        assert!(parse_url("http://:8080").is_err());
+    }
+
+    #[test]
+    fn url_is_normalized() {
+        assert_eq!(parse_url("http://host").unwrap(), "http://host");
+        assert_eq!(parse_url("http://host:8080/").unwrap(), "http://host:8080");
+        assert_eq!(
+            parse_url(" https://host/qbt/ ").unwrap(),
+            "https://host/qbt"
+        );
+    }
+
+    #[test]
+    fn url_without_scheme_is_prefixed() {
+        assert_eq!(parse_url("host:8080").unwrap(), "http://host:8080");
+    }
+
+    #[test]
+    fn url_with_other_scheme_or_query_is_rejected() {
+        assert!(parse_url("ftp://host").is_err());
+        assert!(parse_url("http://host/?a=1").is_err());
+        assert!(parse_url("http://host/#top").is_err());
+    }
+}
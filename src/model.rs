@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::IntoDeserializer, Deserialize, Serialize};
 
-use crate::humanize::{humanize_bytes, humanize_eta, humanize_percentage};
+use crate::humanize::{
+    humanize_bytes, humanize_duration, humanize_eta, humanize_percentage, humanize_ratio,
+    humanize_speed, humanize_up_limit, is_infinite_eta,
+};
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 pub enum SpeedLimitsMode {
@@ -28,27 +31,39 @@ pub struct SetSpeedLimit {
 // TODO: partial data
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct TransferInfo {
-    pub dl_info_speed: i64,                  // Global download rate (bytes/s)
-    pub dl_info_data: i64,                   // Data downloaded this session (bytes)
-    pub up_info_speed: i64,                  // Global upload rate (bytes/s)
-    pub up_info_data: i64,                   // Data uploaded this session (bytes)
-    pub dl_rate_limit: i64,                  // Download rate limit (bytes/s)
-    pub up_rate_limit: i64,                  // Upload rate limit (bytes/s)
-    pub dht_nodes: i64,                      // DHT nodes connected to
+    pub dl_info_speed: i64, // Global download rate (bytes/s)
+    pub dl_info_data: i64,  // Data downloaded this session (bytes)
+    pub up_info_speed: i64, // Global upload rate (bytes/s)
+    pub up_info_data: i64,  // Data uploaded this session (bytes)
+    pub dl_rate_limit: i64, // Download rate limit (bytes/s)
+    pub up_rate_limit: i64, // Upload rate limit (bytes/s)
+    pub dht_nodes: i64,     // DHT nodes connected to
+    #[serde(default)]
     pub connection_status: ConnectionStatus, // Connection status. See possible values here below
     #[serde(default = "bool::default")]
     pub use_alt_speed_limits: bool, // True if alternative speed limits are enabled
 }
 
 impl TransferInfo {
-    pub fn to_stats_string(&self, host: &str) -> String {
+    pub fn session_data(&self) -> i64 {
+        self.dl_info_data + self.up_info_data
+    }
+
+    pub fn to_stats_segments(
+        &self,
+        segments: &[StatsSegment],
+        host: &str,
+        preferences: &Preferences,
+        speed_in_bits: bool,
+    ) -> Vec<String> {
         let connection_status_icon = match self.connection_status {
             ConnectionStatus::Connected => "🔗",
             ConnectionStatus::Firewalled => "🌢",
             ConnectionStatus::Disconnected => "⏏",
+            ConnectionStatus::Unknown => "?",
         };
-        let human_dl_speed = humanize_bytes(self.dl_info_speed as f64) + "/s";
-        let human_up_speed = humanize_bytes(self.up_info_speed as f64) + "/s";
+        let human_dl_speed = humanize_speed(self.dl_info_speed as f64, speed_in_bits);
+        let human_up_speed = humanize_speed(self.up_info_speed as f64, speed_in_bits);
         let human_dl_data = humanize_bytes(self.dl_info_data as f64);
         let human_up_data = humanize_bytes(self.up_info_data as f64);
         let speed_limits_mode = if self.use_alt_speed_limits {
@@ -56,17 +71,393 @@ impl TransferInfo {
         } else {
             "GLO"
         };
+        // mode may switch on its own
+        let speed_limits_schedule = match preferences.alt_speed_schedule() {
+            Some(schedule) => format!(" ⏲ {schedule}"),
+            None => "".to_owned(),
+        };
         let alt_dl_rate_limit = if self.use_alt_speed_limits {
-            format!("[{}]", humanize_bytes(self.dl_rate_limit as f64) + "/s")
+            format!(
+                " [{}]",
+                humanize_speed(self.dl_rate_limit as f64, speed_in_bits)
+            )
         } else {
             "".to_owned()
         };
         let alt_up_rate_limit = if self.use_alt_speed_limits {
-            format!("[{}]", humanize_bytes(self.up_rate_limit as f64) + "/s")
+            format!(
+                " [{}]",
+                humanize_speed(self.up_rate_limit as f64, speed_in_bits)
+            )
         } else {
             "".to_owned()
         };
-        format!("DHT: {} nodes | {host} {connection_status_icon} | ⯯ {human_dl_speed} {alt_dl_rate_limit} ({human_dl_data}) | 🠝 {human_up_speed} {alt_up_rate_limit} ({human_up_data}) | {speed_limits_mode} |", self.dht_nodes)
+
+        segments
+            .iter()
+            .map(|segment| match segment {
+                StatsSegment::Dht => format!("DHT: {} nodes", self.dht_nodes),
+                StatsSegment::Host => format!("{host} {connection_status_icon}"),
+                StatsSegment::Download => {
+                    format!("⯯ {human_dl_speed}{alt_dl_rate_limit} ({human_dl_data})")
+                }
+                StatsSegment::Upload => {
+                    format!("🠝 {human_up_speed}{alt_up_rate_limit} ({human_up_data})")
+                }
+                StatsSegment::SpeedLimits => format!("{speed_limits_mode}{speed_limits_schedule}"),
+            })
+            .collect()
+    }
+}
+
+/// Coarse state, every torrent is in exactly one group
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateGroup {
+    Downloading,
+    Seeding,
+    Paused,
+}
+
+impl StateGroup {
+    pub const ALL: [Self; 3] = [Self::Downloading, Self::Seeding, Self::Paused];
+
+    pub fn of(torrent: &TorrentInfo) -> Self {
+        if !torrent.is_running() {
+            Self::Paused
+        } else if torrent.progress >= 1.0 {
+            Self::Seeding
+        } else {
+            Self::Downloading
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Downloading => "Downloading",
+            Self::Seeding => "Seeding",
+            Self::Paused => "Paused",
+        }
+    }
+}
+
+/// Row of the torrents table, headers only when grouped by category or added date
+pub enum TableRow<'a> {
+    Category(CategoryGroup<'a>),
+    Added(AddedGroup),
+    Torrent(&'a TorrentInfo),
+}
+
+impl TableRow<'_> {
+    /// Added date headers are only titles, category headers can be collapsed
+    pub fn is_selectable(&self) -> bool {
+        !matches!(self, Self::Added(_))
+    }
+}
+
+/// Header of the torrents of one category, with their totals
+pub struct CategoryGroup<'a> {
+    pub name: &'a str,
+    pub torrents_n: usize,
+    pub dlspeed: i64,
+    pub upspeed: i64,
+    pub is_collapsed: bool,
+}
+
+impl CategoryGroup<'_> {
+    /// Same columns as `TorrentInfo::to_row`
+    pub fn to_row(&self, speed_in_bits: bool) -> Vec<String> {
+        let arrow = if self.is_collapsed { "▸" } else { "▾" };
+        let name = if self.name.is_empty() {
+            "Uncategorized"
+        } else {
+            self.name
+        };
+
+        vec![
+            arrow.to_owned(),
+            String::new(),
+            format!("{name} ({})", self.torrents_n),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            humanize_speed(self.dlspeed as f64, speed_in_bits),
+            humanize_speed(self.upspeed as f64, speed_in_bits),
+            String::new(),
+        ]
+    }
+}
+
+/// Rolling windows back from now, the local timezone is not known
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AddedGroup {
+    LastDay,
+    DayBefore,
+    LastWeek,
+    Older,
+}
+
+impl AddedGroup {
+    pub fn from_timestamp(added_on: i64, now: i64) -> Self {
+        const DAY: i64 = 24 * 60 * 60;
+        let age = now - added_on;
+        if age < DAY {
+            Self::LastDay
+        } else if age < 2 * DAY {
+            Self::DayBefore
+        } else if age < 7 * DAY {
+            Self::LastWeek
+        } else {
+            Self::Older
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::LastDay => "Last 24 hours",
+            Self::DayBefore => "1–2 days ago",
+            Self::LastWeek => "Last 7 days",
+            Self::Older => "Older",
+        }
+    }
+
+    /// Same columns as `TorrentInfo::to_row`, the title in the first one
+    pub fn to_row(self) -> Vec<String> {
+        let mut row = vec![String::new(); 10];
+        row[0] = self.title().to_owned();
+        row
+    }
+}
+
+/// Parts of the stats bar
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsSegment {
+    Dht,
+    Host,
+    Download,
+    Upload,
+    SpeedLimits,
+}
+
+impl StatsSegment {
+    pub const ALL: [Self; 5] = [
+        Self::Dht,
+        Self::Host,
+        Self::Download,
+        Self::Upload,
+        Self::SpeedLimits,
+    ];
+}
+
+#[derive(Debug, Default)]
+pub struct TorrentsSummary {
+    pub downloading_n: usize,
+    pub seeding_n: usize,
+    // time until every running download is finished:
+    // downloads run in parallel, so it's the max eta, not the sum
+    pub eta: i64,
+}
+
+/// All-time totals of the torrents still in the list,
+/// removed torrents do not count
+#[derive(Debug, Default)]
+pub struct RatioSummary {
+    pub downloaded: i64,
+    pub uploaded: i64,
+    pub seeding_time: i64, // seconds
+    pub torrents_n: usize,
+    pub seeding_n: usize,
+}
+
+impl RatioSummary {
+    pub fn from_torrents<'a>(torrents: impl Iterator<Item = &'a TorrentInfo>) -> Self {
+        let mut summary = Self::default();
+        for torrent in torrents {
+            summary.torrents_n += 1;
+            summary.downloaded += torrent.downloaded;
+            summary.uploaded += torrent.uploaded;
+            summary.seeding_time += torrent.seeding_time;
+            if torrent.is_running() && torrent.state.is_seeding() {
+                summary.seeding_n += 1;
+            }
+        }
+        summary
+    }
+
+    // like qBittorrent, nothing downloaded means infinite
+    pub fn ratio(&self) -> Option<f64> {
+        if self.downloaded > 0 {
+            Some(self.uploaded as f64 / self.downloaded as f64)
+        } else {
+            None
+        }
+    }
+
+    pub fn to_page(&self) -> String {
+        let ratio = match self.ratio() {
+            Some(ratio) => humanize_ratio(ratio),
+            None => "∞".to_owned(),
+        };
+        let average_seeding_time = if self.torrents_n > 0 {
+            humanize_duration(self.seeding_time / self.torrents_n as i64)
+        } else {
+            "-".to_owned()
+        };
+
+        vec![
+            format!("Global ratio: {ratio}"),
+            format!("Downloaded: {}", humanize_bytes(self.downloaded as f64)),
+            format!("Uploaded: {}", humanize_bytes(self.uploaded as f64)),
+            format!(
+                "Total seeding time: {}",
+                humanize_duration(self.seeding_time)
+            ),
+            format!("Average seeding time: {average_seeding_time}"),
+            format!("Seeding now: {} of {}", self.seeding_n, self.torrents_n),
+        ]
+        .join("\n")
+    }
+}
+
+impl TorrentsSummary {
+    pub fn from_torrents<'a>(torrents: impl Iterator<Item = &'a TorrentInfo>) -> Self {
+        let mut summary = Self::default();
+        for torrent in torrents.filter(|t| t.is_running()) {
+            if torrent.state.is_downloading() {
+                summary.downloading_n += 1;
+                let eta = if is_infinite_eta(torrent.eta) {
+                    i64::MAX
+                } else {
+                    torrent.eta
+                };
+                summary.eta = std::cmp::max(summary.eta, eta);
+            } else if torrent.state.is_seeding() {
+                summary.seeding_n += 1;
+            }
+        }
+        summary
+    }
+
+    pub fn to_summary_string(&self) -> String {
+        let eta = if self.downloading_n == 0 {
+            "-".to_owned()
+        } else {
+            humanize_eta(self.eta)
+        };
+        format!(
+            " ⯯ {} downloading | 🠝 {} seeding | ETA for all: {eta} ",
+            self.downloading_n, self.seeding_n
+        )
+    }
+}
+
+// only the used subset of /app/preferences
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub scheduler_enabled: bool, // True if alternative limits should be applied according to schedule
+    pub schedule_from_hour: i64, // Scheduler starting hour
+    pub schedule_from_min: i64,  // Scheduler starting minute
+    pub schedule_to_hour: i64,   // Scheduler ending hour
+    pub schedule_to_min: i64,    // Scheduler ending minute
+    pub save_path: String,       // Default save path for torrents, separated by slashes
+    pub listen_port: i64,        // Port for incoming connections
+    pub max_connec: i64,         // Maximum global number of simultaneous connections
+    pub max_connec_per_torrent: i64, // Maximum number of simultaneous connections per torrent
+    pub max_uploads: i64,        // Maximum number of upload slots
+    pub max_uploads_per_torrent: i64, // Maximum number of upload slots per torrent
+    pub queueing_enabled: bool,  // True if torrent queuing is enabled
+    pub max_active_downloads: i64, // Maximum number of active simultaneous downloads
+    pub max_active_uploads: i64, // Maximum number of active simultaneous uploads
+    pub max_active_torrents: i64, // Maximum number of active simultaneous downloads and uploads
+    pub max_ratio_enabled: bool, // True if share ratio limit is enabled
+    pub max_ratio: f64,          // Get the global share ratio limit
+    pub max_seeding_time_enabled: bool, // True if share time limit is enabled
+    pub max_seeding_time: i64,   // Number of minutes to seed a torrent
+    pub dht: bool,               // True if DHT is enabled
+    pub pex: bool,               // True if PeX is enabled
+    pub lsd: bool,               // True if LSD is enabled
+}
+
+// -1 (and 0 for some of the limits) means "no limit"
+fn humanize_limit(v: i64) -> String {
+    if v <= 0 {
+        "∞".to_owned()
+    } else {
+        v.to_string()
+    }
+}
+
+fn humanize_toggle(v: bool) -> &'static str {
+    if v {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+impl Preferences {
+    pub fn alt_speed_schedule(&self) -> Option<String> {
+        if !self.scheduler_enabled {
+            return None;
+        }
+
+        Some(format!(
+            "ALT {:02}:{:02}-{:02}:{:02}",
+            self.schedule_from_hour,
+            self.schedule_from_min,
+            self.schedule_to_hour,
+            self.schedule_to_min
+        ))
+    }
+
+    pub fn to_page(&self) -> String {
+        let max_ratio = if self.max_ratio_enabled {
+            format!("{:.2}", self.max_ratio)
+        } else {
+            "∞".to_owned()
+        };
+        let max_seeding_time = if self.max_seeding_time_enabled {
+            humanize_duration(self.max_seeding_time * 60)
+        } else {
+            "∞".to_owned()
+        };
+        let queueing = if self.queueing_enabled {
+            format!(
+                "{} downloads, {} uploads, {} torrents",
+                humanize_limit(self.max_active_downloads),
+                humanize_limit(self.max_active_uploads),
+                humanize_limit(self.max_active_torrents)
+            )
+        } else {
+            "off".to_owned()
+        };
+
+        vec![
+            format!("Default save path: {}", self.save_path),
+            format!("Listening port: {}", self.listen_port),
+            format!(
+                "Max connections: {} (per torrent: {})",
+                humanize_limit(self.max_connec),
+                humanize_limit(self.max_connec_per_torrent)
+            ),
+            format!(
+                "Max upload slots: {} (per torrent: {})",
+                humanize_limit(self.max_uploads),
+                humanize_limit(self.max_uploads_per_torrent)
+            ),
+            format!("Queueing: {queueing}"),
+            format!("Global ratio limit: {max_ratio}"),
+            format!("Global seeding time limit: {max_seeding_time}"),
+            format!(
+                "DHT: {} | PeX: {} | LSD: {}",
+                humanize_toggle(self.dht),
+                humanize_toggle(self.pex),
+                humanize_toggle(self.lsd)
+            ),
+        ]
+        .join("\n")
     }
 }
 
@@ -77,15 +468,20 @@ pub enum ConnectionStatus {
     #[serde(rename = "firewalled")]
     Firewalled,
     #[serde(rename = "disconnected")]
-    #[default]
     Disconnected,
+    // not received yet, or a value this version doesn't know
+    #[default]
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Clone, Debug, Serialize)]
+// NOTE: values are url-encoded by reqwest (.query()/.form()), always pass raw names
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetTorrentListParams {
-    filter: Option<String>, // Filter torrent list by state. Allowed state filters: all, downloading, seeding, completed, paused, active, inactive, resumed, stalled, stalled_uploading, stalled_downloading, errored
-    category: Option<String>, // Get torrents with the given category (empty string means "without category"; no "category" parameter means "any category" <- broken until #11748 is resolved). Remember to URL-encode the category name. For example, My category becomes My%20category
-    tag: Option<String>, // Get torrents with the given tag (empty string means "without tag"; no "tag" parameter means "any tag". Remember to URL-encode the category name. For example, My tag becomes My%20tag
+    filter: Option<StateFilter>, // Filter torrent list by state. Allowed state filters: all, downloading, seeding, completed, paused, active, inactive, resumed, stalled, stalled_uploading, stalled_downloading, errored
+    category: Option<String>, // Get torrents with the given category (empty string means "without category"; no "category" parameter means "any category" <- broken until #11748 is resolved)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>, // Get torrents with the given tag (empty string means "without tag"; no "tag" parameter means "any tag"
     sort: Option<String>, // torrents by given key. They can be sorted using any field of the response's JSON array (which are documented below) as the sort key.
     reverse: Option<bool>, // Enable reverse sorting. Defaults to false
     limit: Option<i32>,   // Limit the number of torrents returned
@@ -93,7 +489,73 @@ pub struct GetTorrentListParams {
     hashes: Option<String>, // Filter by hashes. Can contain multiple hashes separated by |
 }
 
-#[derive(Clone, Debug, Deserialize)]
+impl GetTorrentListParams {
+    pub fn with_filter(mut self, filter: StateFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_owned());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_owned());
+        self
+    }
+}
+
+// names match the "filter" param of /torrents/info
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateFilter {
+    #[default]
+    All,
+    Downloading,
+    Seeding,
+    Completed,
+    Paused,
+    Resumed,
+    Active,
+    Inactive,
+    Stalled,
+    StalledUploading,
+    StalledDownloading,
+    Errored,
+}
+
+impl FromStr for StateFilter {
+    type Err = serde::de::value::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(s.into_deserializer())
+    }
+}
+
+impl StateFilter {
+    pub fn matches(&self, torrent: &TorrentInfo) -> bool {
+        use TorrentInfoState as S;
+        let state = torrent.state;
+        match self {
+            Self::All => true,
+            Self::Downloading => state.is_downloading(),
+            Self::Seeding => state.is_seeding(),
+            Self::Completed => state.is_seeding() || state == S::PausedUp,
+            Self::Paused => !torrent.is_running(),
+            Self::Resumed => torrent.is_running(),
+            Self::Active => torrent.is_transferring(),
+            Self::Inactive => !torrent.is_transferring(),
+            Self::Stalled => state == S::StalledDl || state == S::StalledUp,
+            Self::StalledUploading => state == S::StalledUp,
+            Self::StalledDownloading => state == S::StalledDl,
+            Self::Errored => state.is_errored(),
+        }
+    }
+}
+
+// field names are the same as in qBittorrent
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TorrentInfo {
     pub added_on: i64,
     pub amount_left: i64,
@@ -112,20 +574,126 @@ pub struct TorrentInfo {
     pub num_leechs: u64,     // leechs connected to
     pub num_seeds: u64,      // seeds connected to
     pub progress: f64,
+    #[serde(default)]
+    pub ratio: f64,
+    #[serde(default = "no_ratio_limit")]
+    pub max_ratio: f64, // effective limit, -1 if none
     pub save_path: String,
-    pub size: i64,
+    #[serde(default)]
+    pub seeding_time: i64, // seconds
+    #[serde(default = "no_seeding_time_limit")]
+    pub max_seeding_time: i64, // effective limit in minutes, -1 if none
+    pub size: i64, // selected files only
+    #[serde(default)]
+    pub total_size: i64, // including files that are not downloaded
     pub state: TorrentInfoState,
+    pub tags: String, // comma-separated
+    #[serde(default)]
+    pub uploaded: i64,
     pub upspeed: i64,
+    #[serde(default)]
+    pub up_limit: i64, // bytes/s, 0 or -1 if none
+}
+
+// missing limits are no limits, 0 would be reached right away
+fn no_ratio_limit() -> f64 {
+    -1.0
+}
+
+fn no_seeding_time_limit() -> i64 {
+    -1
+}
+
+/// How soon a download finishes, for the Eta column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EtaUrgency {
+    Soon,
+    Normal,
+    Far,
+    Stalled,
+}
+
+const ETA_SOON: i64 = 10 * 60;
+const ETA_FAR: i64 = 24 * 60 * 60;
+
+/// What Seeds and Peers columns show
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PeerCountsMode {
+    #[default]
+    Both, // connected (swarm)
+    Connected,
+    Swarm,
+}
+
+impl PeerCountsMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Both => Self::Connected,
+            Self::Connected => Self::Swarm,
+            Self::Swarm => Self::Both,
+        }
+    }
+
+    pub fn to_headers(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Both => ("Seeds", "Peers"),
+            Self::Connected => ("Seeds ⇄", "Peers ⇄"),
+            Self::Swarm => ("Seeds Σ", "Peers Σ"),
+        }
+    }
 }
 
 impl TorrentInfo {
-    pub fn to_row(&self) -> Vec<String> {
-        let size_in_bytes = humanize_bytes(self.size as f64);
+    /// Some files are set to "do not download"
+    pub fn is_partial(&self) -> bool {
+        self.total_size > self.size
+    }
+
+    /// None if the torrent is not downloading right now, e.g. paused or queued
+    pub fn eta_urgency(&self) -> Option<EtaUrgency> {
+        if self.state == TorrentInfoState::StalledDl {
+            return Some(EtaUrgency::Stalled);
+        }
+        if !self.state.is_downloading() || !self.state.is_active() {
+            return None;
+        }
+
+        let urgency = if is_infinite_eta(self.eta) {
+            EtaUrgency::Stalled
+        } else if self.eta <= ETA_SOON {
+            EtaUrgency::Soon
+        } else if self.eta >= ETA_FAR {
+            EtaUrgency::Far
+        } else {
+            EtaUrgency::Normal
+        };
+        Some(urgency)
+    }
+
+    pub fn to_row(&self, speed_in_bits: bool, peer_counts: PeerCountsMode) -> Vec<String> {
+        let size_in_bytes = if self.is_partial() {
+            format!(
+                "{}/{}",
+                humanize_bytes(self.size as f64),
+                humanize_bytes(self.total_size as f64)
+            )
+        } else {
+            humanize_bytes(self.size as f64)
+        };
         let progress_percentage = humanize_percentage(self.progress);
-        let seeds_info = format!("{} ({})", self.num_seeds, self.num_complete);
-        let leechs_info = format!("{} ({})", self.num_leechs, self.num_incomplete);
-        let dl_in_bytes_per_sec = humanize_bytes(self.dlspeed as f64) + "/s";
-        let up_in_bytes_per_sec = humanize_bytes(self.upspeed as f64) + "/s";
+        let (seeds_info, leechs_info) = match peer_counts {
+            PeerCountsMode::Both => (
+                format!("{} ({})", self.num_seeds, self.num_complete),
+                format!("{} ({})", self.num_leechs, self.num_incomplete),
+            ),
+            PeerCountsMode::Connected => (self.num_seeds.to_string(), self.num_leechs.to_string()),
+            PeerCountsMode::Swarm => (
+                self.num_complete.to_string(),
+                self.num_incomplete.to_string(),
+            ),
+        };
+        let dl_speed = humanize_speed(self.dlspeed as f64, speed_in_bits);
+        let up_speed = humanize_speed(self.upspeed as f64, speed_in_bits);
         let eta = humanize_eta(self.eta);
 
         vec![
@@ -136,25 +704,90 @@ impl TorrentInfo {
             progress_percentage,
             seeds_info,
             leechs_info,
-            dl_in_bytes_per_sec,
-            up_in_bytes_per_sec,
+            dl_speed,
+            up_speed,
             eta,
         ]
     }
 
-    pub fn to_info_page(&self) -> String {
+    pub fn to_info_page(&self, speed_in_bits: bool) -> String {
+        let max_ratio = if self.max_ratio < 0.0 {
+            "∞".to_owned()
+        } else {
+            format!("{:.2}", self.max_ratio)
+        };
+        let max_seeding_time = if self.max_seeding_time < 0 {
+            "∞".to_owned()
+        } else {
+            humanize_duration(self.max_seeding_time * 60)
+        };
+        let share_limit = if self.is_share_limit_reached() {
+            " (share limit reached)"
+        } else {
+            ""
+        };
+
         vec![
             format!("Name: {}", self.name),
-            format!("Size: {}", humanize_bytes(self.size as f64)),
+            if self.is_partial() {
+                format!(
+                    "Size: {} of {} selected",
+                    humanize_bytes(self.size as f64),
+                    humanize_bytes(self.total_size as f64)
+                )
+            } else {
+                format!("Size: {}", humanize_bytes(self.size as f64))
+            },
+            format!(
+                "Ratio: {} / {max_ratio}{share_limit}",
+                humanize_ratio(self.ratio)
+            ),
+            format!(
+                "Seeding time: {} / {max_seeding_time}",
+                humanize_duration(self.seeding_time)
+            ),
+            format!(
+                "Upload limit: {}",
+                humanize_up_limit(self.up_limit, speed_in_bits)
+            ),
+            format!("Category: {}", self.category),
+            format!("Tags: {}", self.tags),
             format!("Save path: {}", self.save_path),
             format!("Hash: {}", self.hash),
         ]
         .join("\n")
     }
 
+    /// First 8 chars, enough to find it in logs or the Web UI
+    pub fn short_hash(&self) -> &str {
+        self.hash.get(..8).unwrap_or(&self.hash)
+    }
+
+    /// qBittorrent pauses or removes such torrents, depending on its settings
+    pub fn is_share_limit_reached(&self) -> bool {
+        (self.max_ratio >= 0.0 && self.ratio >= self.max_ratio)
+            || (self.max_seeding_time >= 0 && self.seeding_time >= self.max_seeding_time * 60)
+    }
+
+    pub fn tag_list(&self) -> Vec<&str> {
+        split_tags(&self.tags)
+    }
+
+    /// Active in the Web UI's sense: data is moving in either direction,
+    /// so an idle seeding torrent is inactive. Metadata fetching counts too, like in qBittorrent
+    pub fn is_transferring(&self) -> bool {
+        use TorrentInfoState as S;
+        self.dlspeed > 0 || self.upspeed > 0 || matches!(self.state, S::MetaDl | S::ForcedMetaDL)
+    }
+
     pub fn is_running(&self) -> bool {
         self.state != TorrentInfoState::PausedUp && self.state != TorrentInfoState::PausedDl
     }
+
+    /// Running, but not connected to anyone, paused torrents never are
+    pub fn has_no_peers(&self) -> bool {
+        self.is_running() && self.num_seeds + self.num_leechs == 0
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -169,11 +802,19 @@ pub struct TorrentInfoSync {
     pub eta: Option<i64>,
     pub name: Option<String>,
     pub progress: Option<f64>,
+    pub ratio: Option<f64>,
+    pub max_ratio: Option<f64>,
     pub save_path: Option<String>,
+    pub seeding_time: Option<i64>,
+    pub max_seeding_time: Option<i64>,
     pub state: Option<TorrentInfoState>,
     pub size: Option<i64>,
+    pub total_size: Option<i64>,
+    pub tags: Option<String>,
+    pub uploaded: Option<i64>,
     pub dlspeed: Option<i64>,
     pub upspeed: Option<i64>,
+    pub up_limit: Option<i64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -191,7 +832,7 @@ pub struct TransferInfoSync {
 
 // src/base/bittorrent/torrent.h - TorrentState
 // src/webui/api/serialize/serialize_torrent.cpp
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum TorrentInfoState {
     #[serde(rename = "unknown")]
     Unknown = -1,
@@ -245,12 +886,77 @@ pub enum TorrentInfoState {
 }
 
 impl TorrentInfoState {
+    // qBittorrent/src/base/bittorrent/torrentimpl.cpp - isDownloading
+    pub fn is_downloading(&self) -> bool {
+        matches!(
+            self,
+            Self::Downloading
+                | Self::MetaDl
+                | Self::ForcedMetaDL
+                | Self::StalledDl
+                | Self::CheckingDl
+                | Self::PausedDl
+                | Self::QueuedDl
+                | Self::ForcedDl
+        )
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            Self::Downloading
+                | Self::ForcedDl
+                | Self::MetaDl
+                | Self::ForcedMetaDL
+                | Self::Uploading
+                | Self::ForcedUp
+                | Self::CheckingDl
+                | Self::CheckingUp
+                | Self::CheckingResumeData
+                | Self::Moving
+        )
+    }
+
+    pub fn is_fetching_metadata(&self) -> bool {
+        matches!(self, Self::MetaDl | Self::ForcedMetaDL)
+    }
+
+    pub fn is_checking(&self) -> bool {
+        matches!(
+            self,
+            Self::CheckingDl | Self::CheckingUp | Self::CheckingResumeData
+        )
+    }
+
+    pub fn is_errored(&self) -> bool {
+        matches!(self, Self::Unknown | Self::MissingFiles | Self::Error)
+    }
+
+    /// The Web UI has no error message, the state is all there is
+    pub fn error_title(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingFiles => Some("Missing files"),
+            Self::Error => Some("Error (I/O or disk full)"),
+            Self::Unknown => Some("Unknown state"),
+            _ => None,
+        }
+    }
+
+    // qBittorrent/src/base/bittorrent/torrentimpl.cpp - isUploading
+    pub fn is_seeding(&self) -> bool {
+        matches!(
+            self,
+            Self::Uploading | Self::StalledUp | Self::CheckingUp | Self::QueuedUp | Self::ForcedUp
+        )
+    }
+
     pub fn to_icon(&self) -> &'static str {
         // qBittorrent/src/gui/transferlistmodel.cpp
         // qBittorrent/src/icons
         match self {
-            Self::Downloading | Self::ForcedDl | Self::MetaDl | Self::ForcedMetaDL => "⯯",
-            Self::StalledDl => "⯯", // another color
+            Self::Downloading | Self::ForcedDl => "⯯",
+            Self::MetaDl | Self::ForcedMetaDL => "⋯", // magnets without metadata yet
+            Self::StalledDl => "⯯",                   // another color
             Self::StalledUp => "⯭",
             Self::Uploading | Self::ForcedUp => "🠝",
             Self::PausedDl => "⏸",
@@ -269,54 +975,65 @@ impl TorrentInfoState {
 #[derive(Clone, Debug, Deserialize)]
 pub struct TorrentProperties {
     pub save_path: String,             // 	Torrent save path
-    pub creation_date: i32,            // 	Torrent creation date (Unix timestamp)
-    pub piece_size: i32,               // 	Torrent piece size (bytes)
+    pub creation_date: i64,            // 	Torrent creation date (Unix timestamp)
+    pub piece_size: i64,               // 	Torrent piece size (bytes)
     pub comment: String,               // 	Torrent comment
-    pub total_wasted: i32,             // 	Total data wasted for torrent (bytes)
-    pub total_uploaded: i32,           // 	Total data uploaded for torrent (bytes)
-    pub total_uploaded_session: i32,   // 	Total data uploaded this session (bytes)
-    pub total_downloaded: i32,         // 	Total data downloaded for torrent (bytes)
-    pub total_downloaded_session: i32, // 	Total data downloaded this session (bytes)
-    pub up_limit: i32,                 // 	Torrent upload limit (bytes/s)
-    pub dl_limit: i32,                 // 	Torrent download limit (bytes/s)
-    pub time_elapsed: i32,             // 	Torrent elapsed time (seconds)
-    pub seeding_time: i32,             // 	Torrent elapsed time while complete (seconds)
-    pub nb_connections: i32,           // 	Torrent connection count
-    pub nb_connections_limit: i32,     // 	Torrent connection count limit
+    pub total_wasted: i64,             // 	Total data wasted for torrent (bytes)
+    pub total_uploaded: i64,           // 	Total data uploaded for torrent (bytes)
+    pub total_uploaded_session: i64,   // 	Total data uploaded this session (bytes)
+    pub total_downloaded: i64,         // 	Total data downloaded for torrent (bytes)
+    pub total_downloaded_session: i64, // 	Total data downloaded this session (bytes)
+    pub up_limit: i64,                 // 	Torrent upload limit (bytes/s)
+    pub dl_limit: i64,                 // 	Torrent download limit (bytes/s)
+    pub time_elapsed: i64,             // 	Torrent elapsed time (seconds)
+    pub seeding_time: i64,             // 	Torrent elapsed time while complete (seconds)
+    pub nb_connections: i64,           // 	Torrent connection count
+    pub nb_connections_limit: i64,     // 	Torrent connection count limit
     pub share_ratio: f64,              // 	Torrent share ratio
-    pub addition_date: i32,            // 	When this torrent was added (unix timestamp)
-    pub completion_date: i32,          // 	Torrent completion date (unix timestamp)
+    pub addition_date: i64,            // 	When this torrent was added (unix timestamp)
+    pub completion_date: i64,          // 	Torrent completion date (unix timestamp)
     pub created_by: String,            // 	Torrent creator
-    pub dl_speed_avg: i32,             // 	Torrent average download speed (bytes/second)
-    pub dl_speed: i32,                 // 	Torrent download speed (bytes/second)
-    pub eta: i32,                      // 	Torrent ETA (seconds)
-    pub last_seen: i32,                // 	Last seen complete date (unix timestamp)
-    pub peers: i32,                    // 	Number of peers connected to
-    pub peers_total: i32,              // 	Number of peers in the swarm
-    pub pieces_have: i32,              // 	Number of pieces owned
-    pub pieces_num: i32,               // 	Number of pieces of the torrent
-    pub reannounce: i32,               // 	Number of seconds until the next announce
-    pub seeds: i32,                    // 	Number of seeds connected to
-    pub seeds_total: i32,              // 	Number of seeds in the swarm
-    pub total_size: i32,               // 	Torrent total size (bytes)
-    pub up_speed_avg: i32,             // 	Torrent average upload speed (bytes/second)
-    pub up_speed: i32,                 // 	Torrent upload speed (bytes/second)
+    pub dl_speed_avg: i64,             // 	Torrent average download speed (bytes/second)
+    pub dl_speed: i64,                 // 	Torrent download speed (bytes/second)
+    pub eta: i64,                      // 	Torrent ETA (seconds)
+    pub last_seen: i64,                // 	Last seen complete date (unix timestamp)
+    pub peers: i64,                    // 	Number of peers connected to
+    pub peers_total: i64,              // 	Number of peers in the swarm
+    pub pieces_have: i64,              // 	Number of pieces owned
+    pub pieces_num: i64,               // 	Number of pieces of the torrent
+    pub reannounce: i64,               // 	Number of seconds until the next announce
+    pub seeds: i64,                    // 	Number of seeds connected to
+    pub seeds_total: i64,              // 	Number of seeds in the swarm
+    pub total_size: i64,               // 	Torrent total size (bytes)
+    pub up_speed_avg: i64,             // 	Torrent average upload speed (bytes/second)
+    pub up_speed: i64,                 // 	Torrent upload speed (bytes/second)
+}
+
+impl TorrentProperties {
+    /// WebUI API has no setter for the per torrent connection limit, so it's read-only
+    pub fn to_connections_line(&self) -> String {
+        format!(
+            "Connections: {} / {}",
+            self.nb_connections,
+            humanize_limit(self.nb_connections_limit)
+        )
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct TorrentFile {
-    pub index: i32, // File index
+    pub index: i32,   // File index
     pub name: String, // File name (including relative path)
-                    // TODO
-                    // pub size: i64,             // File size (bytes)
-                    // pub progress: f64,         // File progress (percentage/100)
-                    // pub priority: Priority,    // File priority. See possible values here below
-                    // pub is_seed: Option<bool>, // True if file is seeding/complete
-                    // pub piece_range: Vec<i32>, // The first number is the starting piece index and the second number is the ending piece index (inclusive)
-                    // pub availability: f64,     // Percentage of file pieces currently available (percentage/100)
+    pub size: i64,    // File size (bytes)
+    pub priority: i32, // File priority, see Priority
+                      // TODO
+                      // pub progress: f64,         // File progress (percentage/100)
+                      // pub is_seed: Option<bool>, // True if file is seeding/complete
+                      // pub piece_range: Vec<i32>, // The first number is the starting piece index and the second number is the ending piece index (inclusive)
+                      // pub availability: f64,     // Percentage of file pieces currently available (percentage/100)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Priority {
     DoNotDownload = 0,
     Normal = 1,
@@ -324,6 +1041,137 @@ pub enum Priority {
     Maximal = 7,
 }
 
+impl Priority {
+    /// Other values are set by old clients, they mean normal
+    pub fn from_value(value: i32) -> Self {
+        match value {
+            0 => Self::DoNotDownload,
+            6 => Self::High,
+            7 => Self::Maximal,
+            _ => Self::Normal,
+        }
+    }
+
+    pub fn higher(self) -> Self {
+        match self {
+            Self::DoNotDownload => Self::Normal,
+            Self::Normal => Self::High,
+            Self::High | Self::Maximal => Self::Maximal,
+        }
+    }
+
+    pub fn lower(self) -> Self {
+        match self {
+            Self::Maximal => Self::High,
+            Self::High => Self::Normal,
+            Self::Normal | Self::DoNotDownload => Self::DoNotDownload,
+        }
+    }
+
+    pub fn to_mark(self) -> &'static str {
+        match self {
+            Self::DoNotDownload => "[ ]",
+            Self::Normal => "[x]",
+            Self::High => "[H]",
+            Self::Maximal => "[M]",
+        }
+    }
+}
+
+/// Row of the files view, directories come before their files
+pub enum FileRow<'a> {
+    Dir {
+        name: &'a str, // last part of the path
+        depth: usize,
+        files: Vec<&'a TorrentFile>, // recursively
+    },
+    File {
+        file: &'a TorrentFile,
+        depth: usize,
+    },
+}
+
+impl<'a> FileRow<'a> {
+    /// All files a priority change applies to
+    pub fn files(&self) -> Vec<&'a TorrentFile> {
+        match self {
+            Self::Dir { files, .. } => files.clone(),
+            Self::File { file, .. } => vec![file],
+        }
+    }
+
+    /// None if the files of a directory differ
+    pub fn priority(&self) -> Option<Priority> {
+        let files = self.files();
+        let first = Priority::from_value(files.first()?.priority);
+        files
+            .iter()
+            .all(|f| Priority::from_value(f.priority) == first)
+            .then_some(first)
+    }
+
+    pub fn to_line(&self) -> String {
+        let mark = self.priority().map_or("[~]", Priority::to_mark);
+        match self {
+            Self::Dir { name, depth, files } => {
+                let size: i64 = files.iter().map(|f| f.size).sum();
+                format!(
+                    "{}{mark} {name}/ ({})",
+                    "  ".repeat(*depth),
+                    humanize_bytes(size as f64)
+                )
+            }
+            Self::File { file, depth } => {
+                let name = file.name.rsplit('/').next().unwrap_or(&file.name);
+                format!(
+                    "{}{mark} {name} ({})",
+                    "  ".repeat(*depth),
+                    humanize_bytes(file.size as f64)
+                )
+            }
+        }
+    }
+}
+
+/// Tree of the relative paths, sorted by path
+pub fn file_rows(files: &[TorrentFile]) -> Vec<FileRow<'_>> {
+    let mut sorted: Vec<&TorrentFile> = files.iter().collect();
+    sorted.sort_by(|a, b| a.name.split('/').cmp(b.name.split('/')));
+
+    let mut rows = Vec::new();
+    let mut prev_dirs: Vec<&str> = Vec::new();
+    for file in sorted.iter() {
+        let mut parts: Vec<&str> = file.name.split('/').collect();
+        parts.pop();
+
+        let common = prev_dirs
+            .iter()
+            .zip(parts.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        for depth in common..parts.len() {
+            let prefix = format!("{}/", parts[..=depth].join("/"));
+            rows.push(FileRow::Dir {
+                name: parts[depth],
+                depth,
+                files: sorted
+                    .iter()
+                    .filter(|f| f.name.starts_with(&prefix))
+                    .copied()
+                    .collect(),
+            });
+        }
+
+        rows.push(FileRow::File {
+            file,
+            depth: parts.len(),
+        });
+        prev_dirs = parts;
+    }
+
+    rows
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct GetTorrentFilesParams {
     hash: String,
@@ -335,6 +1183,80 @@ impl From<String> for GetTorrentFilesParams {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct GetTorrentTrackersParams {
+    hash: String,
+}
+
+impl From<String> for GetTorrentTrackersParams {
+    fn from(hash: String) -> Self {
+        Self { hash }
+    }
+}
+
+// only the used subset of /torrents/trackers
+#[derive(Clone, Debug, Deserialize)]
+pub struct TrackerInfo {
+    pub url: String,
+    pub status: i64, // 0 disabled (DHT, PeX, LSD), 1 not contacted yet, 2 working, 3 updating, 4 not working
+}
+
+// long announce urls carry passkeys, the host is enough to tell them apart
+const TRACKER_URL_MAX_CHARS: usize = 60;
+
+impl TrackerInfo {
+    pub fn status_title(&self) -> &'static str {
+        match self.status {
+            0 => "disabled",
+            1 => "not contacted yet",
+            2 => "working",
+            3 => "updating",
+            _ => "not working",
+        }
+    }
+
+    pub fn to_info_line(&self) -> String {
+        let url = if self.url.chars().count() > TRACKER_URL_MAX_CHARS {
+            let truncated: String = self.url.chars().take(TRACKER_URL_MAX_CHARS - 1).collect();
+            format!("{truncated}…")
+        } else {
+            self.url.clone()
+        };
+        format!("  {url} ({})", self.status_title())
+    }
+}
+
+/// Trackers section of the info page, DHT, PeX and LSD are left out
+pub fn trackers_info_page(trackers: &[TrackerInfo]) -> String {
+    let lines: Vec<String> = trackers
+        .iter()
+        .filter(|t| t.status != 0)
+        .map(TrackerInfo::to_info_line)
+        .collect();
+    if lines.is_empty() {
+        return "Trackers: none".to_owned();
+    }
+
+    format!("Trackers:\n{}", lines.join("\n"))
+}
+
+/// Real trackers only, torrents without them (DHT only) are not counted
+pub fn has_no_working_tracker(trackers: &[TrackerInfo]) -> bool {
+    let mut trackers = trackers.iter().filter(|t| t.status != 0).peekable();
+    trackers.peek().is_some() && trackers.all(|t| t.status == 4)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GetTorrentPropertiesParams {
+    hash: String,
+}
+
+impl From<String> for GetTorrentPropertiesParams {
+    fn from(hash: String) -> Self {
+        Self { hash }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Category {
     pub name: String,
@@ -342,6 +1264,77 @@ pub struct Category {
     pub save_path: String,
 }
 
+/// Options of the add form, for URLs and files alike
+#[derive(Clone, Debug, Default)]
+pub struct AddTorrentOptions {
+    pub paused: bool,
+    pub category: Option<String>,  // None if empty, the default one
+    pub save_path: Option<String>, // None if empty, the category's or the default one
+}
+
+impl AddTorrentOptions {
+    pub fn new(paused: bool, category: &str, save_path: &str) -> Self {
+        let non_empty = |value: &str| Some(value.trim().to_owned()).filter(|v| !v.is_empty());
+        Self {
+            paused,
+            category: non_empty(category),
+            save_path: non_empty(save_path),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AddTorrentPayload {
+    pub urls: String, // URLs or magnets separated with newlines
+    pub paused: bool,
+    pub stopped: bool, // same as paused, qBittorrent 5 renamed it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub savepath: Option<String>,
+}
+
+impl AddTorrentPayload {
+    pub fn new(urls: String, options: AddTorrentOptions) -> Self {
+        Self {
+            urls,
+            paused: options.paused,
+            stopped: options.paused,
+            category: options.category,
+            savepath: options.save_path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AddTrackersPayload {
+    pub hash: String,
+    pub urls: String, // separated with newlines
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SetCategoryPayload {
+    pub hashes: String,
+    pub category: String, // empty to remove
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CategoryPayload {
+    pub category: String,
+    #[serde(rename = "savePath")]
+    pub save_path: String, // empty for the default one
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RemoveCategoriesPayload {
+    pub categories: String, // separated by new lines
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TagsListPayload {
+    pub tags: String, // comma separated
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct DeleteTorrentParams {
     pub hashes: String,
@@ -357,6 +1350,8 @@ pub struct MainData {
     pub torrents_removed: Option<Vec<String>>,
     pub categories: Option<HashMap<String, Category>>,
     pub categories_removed: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+    pub tags_removed: Option<Vec<String>>,
     pub server_state: Option<TransferInfoSync>,
 }
 
@@ -365,6 +1360,89 @@ pub struct GetMainDataParams {
     pub rid: i64,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct TorrentPeers {
+    pub rid: i64,
+    pub full_update: Option<bool>,
+    pub peers: Option<HashMap<String, PeerInfoSync>>, // key: "<ip>:<port>"
+    pub peers_removed: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GetTorrentPeersParams {
+    pub hash: String,
+    pub rid: i64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PeerInfo {
+    pub client: String,
+    pub connection: String,
+    pub country_code: String,
+    pub dl_speed: i64,
+    pub downloaded: i64,
+    pub flags: String,
+    pub ip: String,
+    pub port: u16,
+    pub progress: f64,
+    pub up_speed: i64,
+    pub uploaded: i64,
+}
+
+impl PeerInfo {
+    pub fn update(&mut self, info: PeerInfoSync) {
+        macro_rules! replace_if_some {
+            ($name:ident) => {
+                if let Some(v) = info.$name {
+                    self.$name = v;
+                }
+            };
+        }
+        replace_if_some!(client);
+        replace_if_some!(connection);
+        replace_if_some!(country_code);
+        replace_if_some!(dl_speed);
+        replace_if_some!(downloaded);
+        replace_if_some!(flags);
+        replace_if_some!(ip);
+        replace_if_some!(port);
+        replace_if_some!(progress);
+        replace_if_some!(up_speed);
+        replace_if_some!(uploaded);
+    }
+
+    pub fn to_row(&self, speed_in_bits: bool) -> Vec<String> {
+        vec![
+            format!("{}:{}", self.ip, self.port),
+            self.country_code.to_uppercase(),
+            self.connection.clone(),
+            self.flags.clone(),
+            self.client.clone(),
+            humanize_percentage(self.progress),
+            humanize_speed(self.dl_speed as f64, speed_in_bits),
+            humanize_speed(self.up_speed as f64, speed_in_bits),
+            humanize_bytes(self.downloaded as f64),
+            humanize_bytes(self.uploaded as f64),
+        ]
+    }
+}
+
+// only changed fields are present in incremental updates
+#[derive(Clone, Debug, Deserialize)]
+pub struct PeerInfoSync {
+    pub client: Option<String>,
+    pub connection: Option<String>,
+    pub country_code: Option<String>,
+    pub dl_speed: Option<i64>,
+    pub downloaded: Option<i64>,
+    pub flags: Option<String>,
+    pub ip: Option<String>,
+    pub port: Option<u16>,
+    pub progress: Option<f64>,
+    pub up_speed: Option<i64>,
+    pub uploaded: Option<i64>,
+}
+
 #[derive(Serialize)]
 pub struct Hashes {
     pub hashes: String,
@@ -378,6 +1456,63 @@ impl From<&[&str]> for Hashes {
     }
 }
 
+#[derive(Serialize)]
+pub struct ForceStartPayload {
+    pub hashes: String,
+    pub value: bool,
+}
+
+impl ForceStartPayload {
+    pub fn new(hashes: &[&str], value: bool) -> Self {
+        Self {
+            hashes: hashes.join("|"),
+            value,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SetLocationPayload {
+    pub hashes: String,
+    pub location: String,
+}
+
+#[derive(Serialize)]
+pub struct FilePriorityPayload {
+    pub hash: String,
+    pub id: String, // file indexes separated with |
+    pub priority: i32,
+}
+
+#[derive(Serialize)]
+pub struct UploadLimitPayload {
+    pub hashes: String,
+    pub limit: i64, // bytes/s, 0 removes the limit
+}
+
+#[derive(Serialize)]
+pub struct TagsPayload {
+    pub hashes: String,
+    pub tags: String,
+}
+
+impl TagsPayload {
+    pub fn new(hash: &str, tags: &[String]) -> Self {
+        Self {
+            hashes: hash.to_owned(),
+            tags: tags.join(","),
+        }
+    }
+}
+
+/// Tags are separated with "," (and optional spaces) in both requests and responses
+pub fn split_tags(tags: &str) -> Vec<&str> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
 #[derive(Serialize)]
 pub struct LoginPayload {
     username: String,
@@ -392,3 +1527,130 @@ impl LoginPayload {
         }
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Paused download with no limits, tests set the fields they need
+    pub fn torrent(hash: &str, name: &str) -> TorrentInfo {
+        serde_json::from_value(serde_json::json!({
+            "added_on": 0,
+            "amount_left": 0,
+            "category": "",
+            "completed": 0,
+            "completion_on": 0,
+            "content_path": "",
+            "dlspeed": 0,
+            "downloaded": 0,
+            "eta": 8640000,
+            "hash": hash,
+            "magnet_uri": "",
+            "name": name,
+            "num_complete": 0,
+            "num_incomplete": 0,
+            "num_leechs": 0,
+            "num_seeds": 0,
+            "progress": 0.0,
+            "ratio": 0.0,
+            "max_ratio": -1.0,
+            "save_path": "",
+            "max_seeding_time": -1,
+            "size": 0,
+            "state": "pausedDL",
+            "tags": "",
+            "upspeed": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn row_speeds_follow_the_mode_and_sizes_stay_in_bytes() {
+        let mut t = torrent("a1", "ubuntu");
+        t.size = 1024 * 1024;
+        t.total_size = t.size;
+        t.dlspeed = 125_000;
+
+        let row = t.to_row(false, PeerCountsMode::Both);
+        assert_eq!(row[3], "1 M");
+        assert_eq!(row[7], "122.1 K/s");
+
+        let row = t.to_row(true, PeerCountsMode::Both);
+        assert_eq!(row[3], "1 M");
+        assert_eq!(row[7], "1 Mbps");
+    }
+
+    #[test]
+    fn missing_share_limits_are_no_limits() {
+        let mut value = serde_json::to_value(torrent("a1", "ubuntu")).unwrap();
+        let map = value.as_object_mut().unwrap();
+        for key in ["ratio", "max_ratio", "max_seeding_time"] {
+            map.remove(key);
+        }
+
+        let t: TorrentInfo = serde_json::from_value(value).unwrap();
+        assert_eq!(t.ratio, 0.0);
+        assert_eq!(t.max_ratio, -1.0);
+        assert_eq!(t.max_seeding_time, -1);
+        assert!(!t.is_share_limit_reached());
+    }
+
+    #[test]
+    fn category_with_special_chars_round_trips() {
+        let params = GetTorrentListParams::default().with_category("TV Shows & More");
+        let query = serde_urlencoded::to_string(&params).unwrap();
+        assert_eq!(query, "category=TV+Shows+%26+More");
+
+        let pairs: Vec<(String, String)> = serde_urlencoded::from_str(&query).unwrap();
+        assert_eq!(
+            pairs,
+            vec![("category".to_owned(), "TV Shows & More".to_owned())]
+        );
+    }
+
+    #[test]
+    fn tag_is_sent_only_when_set() {
+        let query = serde_urlencoded::to_string(GetTorrentListParams::default()).unwrap();
+        assert_eq!(query, "");
+
+        // empty means "without tag"
+        let params = GetTorrentListParams::default().with_tag("");
+        assert_eq!(serde_urlencoded::to_string(&params).unwrap(), "tag=");
+
+        let params = GetTorrentListParams::default().with_tag("linux & bsd");
+        let query = serde_urlencoded::to_string(&params).unwrap();
+        assert_eq!(query, "tag=linux+%26+bsd");
+        let pairs: Vec<(String, String)> = serde_urlencoded::from_str(&query).unwrap();
+        assert_eq!(pairs, vec![("tag".to_owned(), "linux & bsd".to_owned())]);
+    }
+
+    #[test]
+    fn idle_torrents_are_inactive() {
+        let mut stalled = torrent("a", "stalled");
+        stalled.state = TorrentInfoState::StalledUp;
+        let mut uploading = torrent("b", "uploading");
+        uploading.state = TorrentInfoState::Uploading;
+        for t in [&stalled, &uploading] {
+            assert!(StateFilter::Inactive.matches(t));
+            assert!(!StateFilter::Active.matches(t));
+        }
+
+        uploading.upspeed = 1;
+        assert!(StateFilter::Active.matches(&uploading));
+        assert!(!StateFilter::Inactive.matches(&uploading));
+
+        let mut metadata = torrent("c", "metadata");
+        metadata.state = TorrentInfoState::MetaDl;
+        assert!(StateFilter::Active.matches(&metadata));
+        assert!(!StateFilter::Inactive.matches(&metadata));
+    }
+
+    #[test]
+    fn unknown_connection_status_is_not_an_error() {
+        let status: ConnectionStatus = serde_json::from_str(r#""something_new""#).unwrap();
+        assert!(matches!(status, ConnectionStatus::Unknown));
+
+        let status: ConnectionStatus = serde_json::from_str(r#""firewalled""#).unwrap();
+        assert!(matches!(status, ConnectionStatus::Firewalled));
+    }
+}
@@ -0,0 +1,105 @@
+use serde_json::Value;
+
+use crate::model::{PeerCountsMode, TorrentInfo};
+
+const HEADERS: [&str; 10] = [
+    "Category", "State", "Name", "Size", "%", "Seeds", "Peers", "Down", "Up", "Eta",
+];
+
+/// Same columns as the torrents table, padded to the widest cell
+pub fn format_table(torrents: &[TorrentInfo], speed_in_bits: bool) -> String {
+    let rows: Vec<Vec<String>> = torrents
+        .iter()
+        .map(|torrent| {
+            let mut row = torrent.to_row(speed_in_bits, PeerCountsMode::Both);
+            // icons have unreliable width in terminals
+            row[1] = format!("{:?}", torrent.state);
+            row
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = HEADERS.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = std::cmp::max(*width, cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[&str]| -> String {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+
+    let mut lines = vec![format_row(&HEADERS)];
+    for row in &rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        lines.push(format_row(&cells));
+    }
+
+    lines.join("\n")
+}
+
+/// Empty fields means all of them, unknown ones are ignored
+pub fn format_json(torrents: &[TorrentInfo], fields: &[String]) -> serde_json::Result<String> {
+    let values = torrents
+        .iter()
+        .map(|torrent| serde_json::to_value(torrent).map(|value| select_fields(value, fields)))
+        .collect::<serde_json::Result<Vec<Value>>>()?;
+
+    serde_json::to_string(&values)
+}
+
+fn select_fields(value: Value, fields: &[String]) -> Value {
+    match value {
+        Value::Object(mut map) if !fields.is_empty() => {
+            map.retain(|key, _| fields.contains(key));
+            Value::Object(map)
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::tests::torrent;
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn selected_fields_keep_qbittorrent_names() {
+        let value = json!({ "hash": "a1", "name": "ubuntu", "dlspeed": 10 });
+        assert_eq!(
+            select_fields(value, &fields(&["name", "dlspeed"])),
+            json!({ "name": "ubuntu", "dlspeed": 10 })
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let value = json!({ "hash": "a1", "name": "ubuntu" });
+        assert_eq!(
+            select_fields(value, &fields(&["name", "nope"])),
+            json!({ "name": "ubuntu" })
+        );
+    }
+
+    #[test]
+    fn no_fields_means_all_of_them() {
+        let text = format_json(&[torrent("a1", "ubuntu")], &[]).unwrap();
+        let values: Vec<Value> = serde_json::from_str(&text).unwrap();
+        assert_eq!(values[0]["hash"], "a1");
+        assert_eq!(values[0]["num_seeds"], 0);
+        assert_eq!(values[0]["state"], "pausedDL");
+    }
+}
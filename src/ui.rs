@@ -1,12 +1,16 @@
 use std::{
+    collections::HashMap,
     io,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, EventStream},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, EventStream, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -19,17 +23,27 @@ use tokio::{
 use tui::{
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Corner, Direction, Layout, Rect},
-    style::{Modifier, Style},
-    text::{Line, Span, Text},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{
+        block::{Position, Title},
         Block, BorderType, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table, Wrap,
     },
     Frame, Terminal,
 };
 
 use crate::{
-    app::{Action, App, Notification, Route, SortOrder},
-    model::TorrentInfo,
+    app::{
+        find_search_match, row_height, unix_now, Action, AddTorrentField, App, AppListState,
+        ManageFocus, ManagePrompt, Notification, RecheckStep, Route, ScrollableTextState,
+        SelectedCategory, SortOrder,
+    },
+    humanize::{humanize_duration, humanize_up_limit},
+    input::TextInput,
+    model::{
+        file_rows, trackers_info_page, EtaUrgency, FileRow, RatioSummary, StateFilter, StateGroup,
+        StatsSegment, TableRow, TorrentInfoState, TorrentsSummary,
+    },
 };
 
 #[derive(Debug)]
@@ -41,7 +55,12 @@ pub enum UiEvent {
 pub async fn start_ui(app: Arc<Mutex<App>>, ui_rx: Receiver<UiEvent>) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -51,7 +70,8 @@ pub async fn start_ui(app: Arc<Mutex<App>>, ui_rx: Receiver<UiEvent>) -> Result<
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -90,11 +110,127 @@ fn create_centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+fn text_input_line(input: &TextInput, has_focus: bool) -> Line<'_> {
+    if !has_focus {
+        return Line::from(input.value());
+    }
+
+    // cursor is drawn as a reversed char (or space at the end)
+    let cursor_style = Style::default().add_modifier(Modifier::REVERSED);
+    let mut before = String::new();
+    let mut under_cursor = String::from(" ");
+    let mut after = String::new();
+    for (i, c) in input.value().chars().enumerate() {
+        match i.cmp(&input.cursor()) {
+            std::cmp::Ordering::Less => before.push(c),
+            std::cmp::Ordering::Equal => under_cursor = c.to_string(),
+            std::cmp::Ordering::Greater => after.push(c),
+        }
+    }
+
+    Line::from(vec![
+        Span::raw(before),
+        Span::styled(under_cursor, cursor_style),
+        Span::raw(after),
+    ])
+}
+
+// segments that don't fit are dropped from the end, so the line is never cut in the middle
+fn fit_stats_segments(segments: &[String], width: u16) -> String {
+    let mut text = String::new();
+    for segment in segments {
+        let candidate = if text.is_empty() {
+            format!("{segment} |")
+        } else {
+            format!("{text} {segment} |")
+        };
+        if text.is_empty() || Span::raw(candidate.as_str()).width() <= width as usize {
+            text = candidate;
+        } else {
+            break;
+        }
+    }
+    text
+}
+
+const STATUS_COLUMN: usize = 1;
+const NAME_COLUMN: usize = 2;
+const ETA_COLUMN: usize = 9;
+
+const SPINNER_FRAMES: [&str; 4] = ["◐", "◓", "◑", "◒"];
+
+// advances with the wall clock, the screen is redrawn at least every tick
+fn spinner_frame() -> &'static str {
+    let millis = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    SPINNER_FRAMES[(millis / 250) as usize % SPINNER_FRAMES.len()]
+}
+
+// per table row, besides the cell texts
+#[derive(Default)]
+struct RowInfo {
+    is_share_limit_reached: bool,
+    eta_urgency: Option<EtaUrgency>,
+    is_header: bool, // header row of a category or added date group
+}
+
+fn eta_style(urgency: Option<EtaUrgency>) -> Style {
+    match urgency {
+        Some(EtaUrgency::Soon) => Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD),
+        Some(EtaUrgency::Far) => Style::default().add_modifier(Modifier::DIM),
+        Some(EtaUrgency::Stalled) => Style::default().fg(Color::Magenta),
+        Some(EtaUrgency::Normal) | None => Style::default(),
+    }
+}
+
+// every match, the values are lowercase
+fn highlight_search_match<'a>(text: &'a str, normal: &str, dotted: &str) -> Line<'a> {
+    let style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(range) = find_search_match(rest, normal, dotted) {
+        if range.is_empty() {
+            break;
+        }
+        spans.push(Span::raw(&rest[..range.start]));
+        spans.push(Span::styled(&rest[range.clone()], style));
+        rest = &rest[range.end..];
+    }
+    spans.push(Span::raw(rest));
+
+    Line::from(spans)
+}
+
+/// Where the segment is in the right aligned single line stats text, if it's shown
+fn stats_segment_rect(segments: &[String], i: usize, text: &str, rect: Rect) -> Option<Rect> {
+    let inner_width = rect.width.saturating_sub(2) as usize;
+    let text_width = Span::raw(text).width();
+    if text_width > inner_width {
+        return None;
+    }
+
+    // each segment is followed by " |" and separated by a space
+    let offset: usize = segments[..i]
+        .iter()
+        .map(|s| Span::raw(s.as_str()).width() + 3)
+        .sum();
+    let width = Span::raw(segments[i].as_str()).width();
+    if offset + width > text_width {
+        return None;
+    }
+
+    let x = rect.x + 1 + (inner_width - text_width + offset) as u16;
+    Some(Rect::new(x, rect.y + 1, width as u16, 1))
+}
+
 fn draw_torrents<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
 
     let should_show_search_block =
-        app.current_route == Route::Search || !app.search_value.is_empty();
+        app.current_route == Route::Search || !app.search_input.is_empty();
 
     let constraints = if should_show_search_block {
         [
@@ -127,28 +263,123 @@ fn draw_torrents<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     };
 
     if should_show_search_block {
-        let mut search_value = app.search_value.clone();
-        if app.current_route == Route::Search {
-            search_value.push('_');
-        }
-
         let search_title = if let Route::Search = app.current_route {
             "Search (Enter - apply, Esc - discard)"
         } else {
             ""
         };
 
-        let text = Paragraph::new(vec![Line::from(search_value.as_str())])
-            .block(create_block(search_title, Style::default()))
-            .alignment(Alignment::Left)
-            .wrap(Wrap { trim: true });
+        let text = Paragraph::new(vec![text_input_line(
+            &app.search_input,
+            app.current_route == Route::Search,
+        )])
+        .block(create_block(search_title, Style::default()))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
 
         f.render_widget(text, rects[1]);
     }
 
-    let stats_text = app.transfer_info.to_stats_string(&app.host);
+    let stats_segments = app.transfer_info.to_stats_segments(
+        app.config.stats_segments(),
+        &app.host,
+        &app.preferences,
+        app.config.speed_in_bits,
+    );
+    let stats_text = fit_stats_segments(&stats_segments, stats_rect.width.saturating_sub(2));
+    app.speed_limits_rect = app
+        .config
+        .stats_segments()
+        .iter()
+        .position(|s| *s == StatsSegment::SpeedLimits)
+        .and_then(|i| stats_segment_rect(&stats_segments, i, &stats_text, stats_rect));
+    let mut stats_title = String::new();
+    if !app.pending_hashes.is_empty() {
+        stats_title.push_str(&format!(" {} Working... ", spinner_frame()));
+    }
+    if app.is_data_cap_exceeded {
+        stats_title.push_str(" ⚠ DATA CAP EXCEEDED ");
+    }
+    if let SelectedCategory::Many(ref names) = app.selected_category {
+        let names: Vec<&str> = names
+            .iter()
+            .map(|name| {
+                if name.is_empty() {
+                    "Uncategorized"
+                } else {
+                    name
+                }
+            })
+            .collect();
+        stats_title.push_str(&format!(" Categories: {} ", names.join(", ")));
+    }
+    if let Some(ref name) = app.smart_filter {
+        stats_title.push_str(&format!(" Filter: {name} "));
+    }
+    if app.state_filter != StateFilter::All {
+        stats_title.push_str(&format!(" State: {:?} ", app.state_filter));
+    }
+    if !app.hidden_state_groups.is_empty() {
+        let hidden: Vec<&str> = StateGroup::ALL
+            .iter()
+            .filter(|group| app.hidden_state_groups.contains(group))
+            .map(|group| group.title())
+            .collect();
+        stats_title.push_str(&format!(" Hidden: {} ", hidden.join(", ")));
+    }
+    if app.hide_fetching_metadata {
+        stats_title.push_str(" Metadata hidden ");
+    }
+    match app.no_working_tracker {
+        Some(ref hashes) => {
+            stats_title.push_str(&format!(" No working tracker: {} ", hashes.len()))
+        }
+        None if app.only_no_working_tracker => stats_title.push_str(" Scanning trackers... "),
+        None => {}
+    }
+    // after a server restart, speeds stay low until it's over
+    let checking_resume_n = app
+        .torrents
+        .iter()
+        .filter(|t| t.state == TorrentInfoState::CheckingResumeData)
+        .count();
+    if checking_resume_n > 0 {
+        stats_title.push_str(&format!(
+            " {} Checking resume data: {checking_resume_n} of {} ",
+            spinner_frame(),
+            app.torrents.len()
+        ));
+    }
+    if app.is_idle() {
+        stats_title.push_str(" ⏸ Idle, syncing paused (press any key) ");
+    }
+    if app.unseen_completed_n > 0 {
+        stats_title.push_str(&format!(" ✓ Completed: {} ", app.unseen_completed_n));
+    }
+    let errored_n = app.torrents.iter().filter(|t| t.state.is_errored()).count();
+    if errored_n > 0 {
+        stats_title.push_str(&format!(" ⚠ Errored: {errored_n} (Ctrl-e) "));
+    }
+    if !app.pending_rechecks.is_empty() {
+        stats_title.push_str(&format!(
+            " Recheck & resume: {} ",
+            app.pending_rechecks.len()
+        ));
+    }
+    if app.show_summary {
+        stats_title
+            .push_str(&TorrentsSummary::from_torrents(app.torrents.iter()).to_summary_string());
+        let no_peers_n = app
+            .get_visible_torrents()
+            .iter()
+            .filter(|t| t.has_no_peers())
+            .count();
+        if no_peers_n > 0 {
+            stats_title.push_str(&format!("| ⊘ {no_peers_n} without peers (Z) "));
+        }
+    }
     let text = Paragraph::new(vec![Line::from(stats_text.as_str())])
-        .block(create_block("", Style::default()))
+        .block(create_block(&stats_title, Style::default()))
         .alignment(Alignment::Right)
         .wrap(Wrap { trim: true });
 
@@ -177,18 +408,24 @@ fn draw_torrents<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         None => "",
     };
 
-    let headers = [
+    let (seeds_header, peers_header) = app.peer_counts_mode.to_headers();
+
+    let mut headers = vec![
         category_header,
         status_icon_header,
         name_header,
         "Size",
         "%",
-        "Seeds",
-        "Peers",
+        seeds_header,
+        peers_header,
         "Down",
         "Up",
         "Eta",
     ];
+    let show_hash_column = app.show_hash_column;
+    if show_hash_column {
+        headers.push("Hash");
+    }
     let cells = headers
         .into_iter()
         .map(|h| Cell::from(h).style(Style::default()));
@@ -196,52 +433,119 @@ fn draw_torrents<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let head_row = Row::new(cells)
         .style(normal_style)
         .height(1)
-        .bottom_margin(1);
+        .bottom_margin(app.torrents_header_height() - 1);
 
-    app.torrents_table.items = app
-        .get_visible_torrents()
+    let speed_in_bits = app.config.speed_in_bits;
+    let peer_counts_mode = app.peer_counts_mode;
+    let table_rows = app.get_table_rows();
+    let row_infos: Vec<RowInfo> = table_rows
+        .iter()
+        .map(|table_row| match table_row {
+            TableRow::Category(_) | TableRow::Added(_) => RowInfo {
+                is_header: true,
+                ..RowInfo::default()
+            },
+            TableRow::Torrent(torrent) => RowInfo {
+                is_share_limit_reached: torrent.is_share_limit_reached(),
+                eta_urgency: torrent.eta_urgency(),
+                is_header: false,
+            },
+        })
+        .collect();
+    app.torrents_table.items = table_rows
         .into_iter()
-        .map(TorrentInfo::to_row)
+        .zip(row_infos.iter())
+        .map(|(table_row, info)| {
+            let mut row = match table_row {
+                TableRow::Category(group) => group.to_row(speed_in_bits),
+                TableRow::Added(group) => group.to_row(),
+                TableRow::Torrent(t) => {
+                    let mut row = t.to_row(speed_in_bits, peer_counts_mode);
+                    if app.pending_hashes.contains(&t.hash) {
+                        row[STATUS_COLUMN] = spinner_frame().to_owned();
+                    }
+                    if show_hash_column {
+                        row.push(t.short_hash().to_owned());
+                    }
+                    row
+                }
+            };
+            if show_hash_column && info.is_header {
+                row.push(String::new());
+            }
+            row
+        })
         .collect();
 
+    let (normal_value, dotted_value) = app.search_values();
     let rows: Vec<Row> = app
         .torrents_table
         .items
         .iter()
-        .map(|item| {
-            let height = item
-                .iter()
-                // NOTE: probably breaks mouse
-                .map(|content| content.chars().filter(|c| *c == '\n').count())
-                .max()
-                .unwrap_or(0)
-                + 1;
-            let cells = item.iter().map(|c| Cell::from(Text::from(c.as_str())));
-            Row::new(cells).height(height as u16).bottom_margin(0)
+        .zip(row_infos)
+        .map(|(item, info)| {
+            let cells = item.iter().enumerate().map(|(i, c)| {
+                let line = if info.is_header {
+                    Line::from(c.as_str())
+                } else if i == NAME_COLUMN && !normal_value.is_empty() {
+                    highlight_search_match(c, &normal_value, &dotted_value)
+                } else if i == ETA_COLUMN {
+                    Line::from(Span::styled(c.as_str(), eta_style(info.eta_urgency)))
+                } else {
+                    Line::from(c.as_str())
+                };
+                Cell::from(line)
+            });
+            let style = if info.is_header {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else if info.is_share_limit_reached {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Row::new(cells)
+                .height(row_height(item))
+                .bottom_margin(0)
+                .style(style)
         })
         .collect();
 
-    let table_constraints = [
-        Constraint::Percentage(10), // category
-        Constraint::Percentage(1),  // status icon
-        Constraint::Percentage(35), // name
-        Constraint::Percentage(8),  // size
-        Constraint::Percentage(5),  // progress
-        Constraint::Percentage(5),  // seeds
-        Constraint::Percentage(5),  // leechs
-        Constraint::Percentage(10), // up
-        Constraint::Percentage(10), // dl
-        Constraint::Percentage(11), // eta
+    // the hash column takes its width from the name
+    let name_width = if show_hash_column { 27 } else { 35 };
+    let mut table_constraints = vec![
+        Constraint::Percentage(10),         // category
+        Constraint::Percentage(1),          // status icon
+        Constraint::Percentage(name_width), // name
+        Constraint::Percentage(8),          // size
+        Constraint::Percentage(5),          // progress
+        Constraint::Percentage(5),          // seeds
+        Constraint::Percentage(5),          // leechs
+        Constraint::Percentage(10),         // up
+        Constraint::Percentage(10),         // dl
+        Constraint::Percentage(11),         // eta
     ];
+    if show_hash_column {
+        table_constraints.push(Constraint::Percentage(8));
+    }
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title("Torrents")
+        .title_alignment(Alignment::Center);
+    let max_torrents = app.config.max_torrents;
+    let visible_n = app.get_visible_torrents().len();
+    if max_torrents > 0 && visible_n > max_torrents {
+        block = block.title(
+            Title::from(format!(
+                " Showing {max_torrents} of {visible_n}, refine your filter "
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Center),
+        );
+    }
     let table = Table::new(rows)
         .header(head_row)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title("Torrents")
-                .title_alignment(Alignment::Center),
-        )
+        .block(block)
         .highlight_style(selected_style)
         .highlight_symbol("> ")
         .widths(&table_constraints);
@@ -256,7 +560,7 @@ fn draw_sort<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     app.sort_list_rect = Some(area);
 
     let block = Block::default()
-        .title("Toggle sort options")
+        .title("Toggle sort options and states")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
@@ -279,11 +583,21 @@ fn draw_sort<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         None => "Status",
     };
 
-    let sort_options = vec![
+    let mut sort_options = vec![
         category_option.to_owned(),
         name_option.to_owned(),
         status_option.to_owned(),
     ];
+    let title_i = sort_options.len();
+    sort_options.push("Show".to_owned());
+    sort_options.extend(StateGroup::ALL.iter().map(|group| {
+        let mark = if app.hidden_state_groups.contains(group) {
+            "[ ]"
+        } else {
+            "[x]"
+        };
+        format!("{mark} {}", group.title())
+    }));
 
     app.sort_list.items = sort_options;
 
@@ -291,7 +605,14 @@ fn draw_sort<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         .sort_list
         .items
         .iter()
-        .map(|c| ListItem::new(c.as_str()))
+        .enumerate()
+        .map(|(i, c)| {
+            if i == title_i {
+                ListItem::new(c.as_str()).style(Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                ListItem::new(c.as_str())
+            }
+        })
         .collect();
 
     let list = List::new(items)
@@ -309,46 +630,96 @@ fn draw_sort<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     f.render_stateful_widget(list, area, &mut app.sort_list.state);
 }
 
-fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+fn draw_commands<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
 
-    let text = include_str!("../docs/keys.md");
+    let area = create_centered_rect(40, 40, size);
+
     let block = Block::default()
-        .title("Help")
+        .title("Run command")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    let paragraph = Paragraph::new(text)
+    let items: Vec<ListItem> = app
+        .commands_list
+        .items
+        .iter()
+        .map(|name| ListItem::new(format!("{name}: {}", app.config.commands[name])))
+        .collect();
+
+    let list = List::new(items)
         .block(block)
-        .alignment(Alignment::Left)
-        .scroll((app.help_state.scroll, 0));
+        .start_corner(Corner::TopLeft)
+        .style(Style::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
 
-    f.render_widget(paragraph, size);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.commands_list.state);
 }
 
-fn draw_categories<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+fn draw_tags<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
-    app.categories_list_rect = Some(size);
+    let area = create_centered_rect(60, 30, size);
 
     let block = Block::default()
-        .title("Select category")
+        .title("Tags (Tab - complete, Enter - apply, Esc - discard)")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    let mut categories = vec!["All".to_string(), "Uncategorized".to_string()];
+    let completion_i = app.tags_completion.as_ref().map(|(_, i)| *i);
+    let mut suggestions = vec![];
+    for (i, tag) in app.get_tag_suggestions().into_iter().enumerate() {
+        let style = if Some(i) == completion_i {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        suggestions.push(Span::styled(tag.clone(), style));
+        suggestions.push(Span::raw(" "));
+    }
 
-    categories.extend_from_slice(&app.categories);
+    let text = vec![
+        text_input_line(&app.tags_input, true),
+        Line::from(""),
+        Line::from(suggestions),
+    ];
 
-    app.categories_list.items = categories.clone();
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
 
-    let items: Vec<ListItem> = app
-        .categories_list
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_set_category<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let area = create_centered_rect(40, 40, size);
+
+    let block = Block::default()
+        .title("Set category")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let mut items: Vec<ListItem> = app
+        .set_category_list
         .items
         .iter()
-        .map(|c| ListItem::new(c.as_str()))
+        .map(|category| {
+            if category.is_empty() {
+                ListItem::new("Uncategorized")
+            } else {
+                ListItem::new(category.as_str())
+            }
+        })
         .collect();
+    items.push(ListItem::new("+ New category..."));
 
     let list = List::new(items)
         .block(block)
@@ -357,145 +728,825 @@ fn draw_categories<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
         .highlight_symbol("> ");
 
-    f.render_stateful_widget(list, size, &mut app.categories_list.state);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.set_category_list.state);
 }
 
-fn draw_notification<B: Backend>(f: &mut Frame<B>, title: &str, text: &str) {
+fn draw_new_category<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
-    let area = create_centered_rect(70, 40, size);
+
+    let width = std::cmp::min(size.width, 60);
+    let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 3);
 
     let block = Block::default()
+        .title("New category (Enter - create and set, Esc - back)")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    let text = vec![
-        Line::from(Span::styled(
-            title,
-            Style::default().add_modifier(Modifier::BOLD),
-        )),
-        Line::from(Span::raw("")),
-        Line::from(Span::raw(text)),
-    ];
-
-    let paragraph = Paragraph::new(text)
-        .style(Style::default())
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
+    let paragraph = Paragraph::new(text_input_line(&app.new_category_input, true)).block(block);
 
-    f.render_widget(Clear, area);
-    f.render_widget(paragraph, area);
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
 }
 
-fn draw_dialog<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+fn draw_add_torrent<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
 
-    let width = std::cmp::min(size.width - 2, 60);
-    let height = 14;
+    let width = std::cmp::min(size.width, 80);
+    let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 5);
 
-    let left = (size.width - width) / 2;
-    let top = size.height / 4;
+    let title = format!(
+        "Add magnet, URL or .torrent path{} (Tab - next field, Enter - add, Up/Down - history, Ctrl-p - paused, Esc - discard)",
+        if app.add_paused { ", paused" } else { "" }
+    );
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
 
-    let rect = Rect::new(left, top, width, height);
+    let fields = [
+        (
+            "Torrent:   ",
+            &app.add_torrent_input,
+            AddTorrentField::Source,
+        ),
+        (
+            "Category:  ",
+            &app.add_category_input,
+            AddTorrentField::Category,
+        ),
+        (
+            "Save path: ",
+            &app.add_save_path_input,
+            AddTorrentField::SavePath,
+        ),
+    ];
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(label, input, field)| {
+            let mut spans = vec![Span::raw(label)];
+            spans.extend(text_input_line(input, field == app.add_torrent_field).spans);
+            Line::from(spans)
+        })
+        .collect();
+    let paragraph = Paragraph::new(lines).block(block);
 
     f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
+}
+
+fn draw_login<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let width = std::cmp::min(size.width, 60);
+    let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 4);
 
     let block = Block::default()
+        .title("Log in (Tab - next field, Enter - log in, Esc - back)")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    f.render_widget(block, rect);
-
-    let vchunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(2)
-        .constraints([Constraint::Min(9), Constraint::Length(3)].as_ref())
-        .split(rect);
-
-    let torrent_name = app.get_selected_torrent().as_ref().unwrap().name.clone();
-    let question = match app.current_action.as_ref().unwrap() {
-        Action::Delete => "Are you sure you want to delete the torrent?",
-        Action::DeleteFiles => "Are you sure you want to delete the torrent AND FILES?",
-    };
-    let text = vec![
-        Line::from(Span::raw(question)),
-        Line::from(Span::raw("")),
-        Line::from(Span::styled(
-            torrent_name,
-            Style::default().add_modifier(Modifier::BOLD),
-        )),
-    ];
-
-    let paragraph = Paragraph::new(text)
-        .wrap(Wrap { trim: true })
-        .alignment(Alignment::Center);
+    let mut username = vec![Span::raw("Username: ")];
+    username.extend(text_input_line(&app.login_username, !app.is_login_password_focused).spans);
+    let masked = app.login_password.masked();
+    let mut password = vec![Span::raw("Password: ")];
+    password.extend(text_input_line(&masked, app.is_login_password_focused).spans);
 
-    f.render_widget(paragraph, vchunks[0]);
+    let paragraph = Paragraph::new(vec![Line::from(username), Line::from(password)]).block(block);
 
-    let hchunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .horizontal_margin(3)
-        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
-        .split(vchunks[1]);
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
+}
 
-    let (style1, style2) = match app.confirm {
-        true => (
-            Style::default().add_modifier(Modifier::REVERSED),
-            Style::default(),
-        ),
-        false => (
-            Style::default(),
-            Style::default().add_modifier(Modifier::REVERSED),
-        ),
-    };
+fn draw_add_trackers<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
 
-    let ok_paragraph = Paragraph::new("Ok")
-        .style(style1)
-        .alignment(Alignment::Center);
+    let width = std::cmp::min(size.width, 80);
+    let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 3);
 
-    f.render_widget(ok_paragraph, hchunks[0]);
+    let block = Block::default()
+        .title(format!(
+            "Add trackers to {} torrent(s) (Enter - add, Esc - discard)",
+            app.add_trackers_hashes.len()
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
 
-    let cancel_paragraph = Paragraph::new("Cancel")
-        .style(style2)
-        .alignment(Alignment::Center);
+    let paragraph = Paragraph::new(text_input_line(&app.add_trackers_input, true)).block(block);
 
-    f.render_widget(cancel_paragraph, hchunks[1]);
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
 }
 
-fn draw_info<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+fn draw_goto<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
 
+    let width = std::cmp::min(size.width, 40);
+    let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 3);
+
     let block = Block::default()
-        .title("Info")
-        .title_alignment(Alignment::Center)
+        .title("Go to row (Enter - apply, Esc - discard)")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    let torrent = app.get_selected_torrent().unwrap();
-    let paragraph = Paragraph::new(torrent.to_info_page())
-        .block(block)
-        .alignment(Alignment::Left)
-        .scroll((app.info_state.scroll, 0));
+    let paragraph = Paragraph::new(text_input_line(&app.goto_input, true)).block(block);
 
-    f.render_widget(paragraph, size);
+    f.render_widget(Clear, rect);
+    f.render_widget(paragraph, rect);
 }
 
-fn draw_files<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+/// Thumb on the right border, only if the text doesn't fit
+fn draw_scroll_indicator<B: Backend>(f: &mut Frame<B>, rect: Rect, state: &ScrollableTextState) {
+    let max_scroll = state.max_scroll();
+    if max_scroll == 0 || state.view_height == 0 {
+        return;
+    }
+
+    let track = state.view_height - 1;
+    let y = rect.y + 1 + (state.scroll as u32 * track as u32 / max_scroll as u32) as u16;
+    let thumb = Rect::new(rect.x + rect.width.saturating_sub(1), y, 1, 1);
+    f.render_widget(Paragraph::new("┃"), thumb);
+}
+
+fn draw_help<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let size = f.size();
-    app.files_list_rect = Some(size);
 
+    let text = include_str!("../docs/keys.md");
     let block = Block::default()
-        .title("Select file")
+        .title("Help")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
 
-    app.files_list.items = app
-        .current_torrent_files
-        .as_ref()
-        .unwrap()
+    app.help_state
+        .resize(text.lines().count(), size.height.saturating_sub(2));
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Left)
+        .scroll((app.help_state.scroll, 0));
+
+    f.render_widget(paragraph, size);
+    draw_scroll_indicator(f, size, &app.help_state);
+}
+
+fn draw_categories<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let mut size = f.size();
+    let has_filter = app.is_filtering_categories || !app.categories_filter.is_empty();
+    if app.is_creating_category {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(4)].as_ref())
+            .split(size);
+        size = chunks[0];
+
+        let block = Block::default()
+            .title("New category (Tab - next field, Enter - create, Esc - cancel)")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let is_save_path_focused = app.is_new_category_save_path_focused;
+        let mut name = vec![Span::raw("Name:      ")];
+        name.extend(text_input_line(&app.new_category_name, !is_save_path_focused).spans);
+        let mut save_path = vec![Span::raw("Save path: ")];
+        save_path.extend(text_input_line(&app.new_category_save_path, is_save_path_focused).spans);
+        let paragraph = Paragraph::new(vec![Line::from(name), Line::from(save_path)]).block(block);
+        f.render_widget(paragraph, chunks[1]);
+    } else if let Some(ref name) = app.editing_category {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+            .split(size);
+        size = chunks[0];
+
+        let block = Block::default()
+            .title(format!(
+                "Save path for {name} (Enter - apply, Esc - discard)"
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let paragraph = Paragraph::new(text_input_line(&app.category_save_path, true)).block(block);
+        f.render_widget(paragraph, chunks[1]);
+    } else if has_filter {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+            .split(size);
+        size = chunks[0];
+
+        let block = Block::default()
+            .title("Filter (Enter - keep, Esc - clear)")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let paragraph = Paragraph::new(text_input_line(
+            &app.categories_filter,
+            app.is_filtering_categories,
+        ))
+        .block(block);
+        f.render_widget(paragraph, chunks[1]);
+    }
+    app.categories_list_rect = Some(size);
+
+    let block = Block::default()
+        .title("Select category")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let mut categories = vec!["All".to_string(), "Uncategorized".to_string()];
+
+    categories.extend_from_slice(&app.categories);
+
+    app.categories_list.items = categories.clone();
+
+    // errored and missing files, by category
+    let mut errored_n: HashMap<&str, usize> = HashMap::new();
+    if app.config.show_category_errors {
+        for torrent in app.torrents.iter().filter(|t| t.state.is_errored()) {
+            *errored_n.entry(torrent.category.as_str()).or_default() += 1;
+        }
+    }
+
+    let indexes = app.filtered_category_indexes();
+    let items: Vec<ListItem> = indexes
+        .iter()
+        .map(|&i| (i, &app.categories_list.items[i]))
+        .map(|(i, c)| {
+            let n = match i {
+                0 => errored_n.values().sum(),
+                1 => errored_n.get("").copied().unwrap_or(0),
+                _ => errored_n.get(c.as_str()).copied().unwrap_or(0),
+            };
+            let is_toggled = match app.selected_category {
+                SelectedCategory::Many(ref names) => match i {
+                    0 => false,
+                    1 => names.contains(""),
+                    _ => names.contains(c),
+                },
+                _ => false,
+            };
+            let mark = if is_toggled { "✓ " } else { "" };
+            if n > 0 {
+                ListItem::new(format!("{mark}{c} ({n}!)"))
+            } else {
+                ListItem::new(format!("{mark}{c}"))
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .start_corner(Corner::TopLeft)
+        .style(Style::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    let selected_pos = app
+        .categories_list
+        .state
+        .selected()
+        .and_then(|i| indexes.iter().position(|&j| j == i));
+    app.categories_view.select(selected_pos);
+    f.render_stateful_widget(list, size, &mut app.categories_view);
+}
+
+fn draw_manage<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let rects = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+        .split(size);
+
+    let create_block = |title, has_focus| {
+        Block::default()
+            .title(Span::styled(
+                title,
+                if has_focus {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                },
+            ))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(if has_focus {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            })
+    };
+
+    // keep the selection in range after removals
+    let clamp_selection = |list: &mut AppListState, len: usize| match list.state.selected() {
+        Some(_) if len == 0 => list.state.select(None),
+        Some(i) if i >= len => list.state.select(Some(len - 1)),
+        None if len > 0 => list.state.select(Some(0)),
+        _ => {}
+    };
+    clamp_selection(&mut app.manage_categories_list, app.categories.len());
+    clamp_selection(&mut app.manage_tags_list, app.tags.len());
+
+    app.manage_categories_list.items = app.categories.clone();
+    let categories: Vec<ListItem> = app
+        .categories
+        .iter()
+        .map(|name| match app.category_save_paths.get(name) {
+            Some(save_path) if !save_path.is_empty() => {
+                ListItem::new(format!("{name} ({save_path})"))
+            }
+            _ => ListItem::new(name.as_str()),
+        })
+        .collect();
+    let categories_list = List::new(categories)
+        .block(create_block(
+            "Categories (n - new, e - edit save path, d - remove)",
+            app.manage_focus == ManageFocus::Categories,
+        ))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(
+        categories_list,
+        rects[0],
+        &mut app.manage_categories_list.state,
+    );
+
+    app.manage_tags_list.items = app.tags.clone();
+    let tags: Vec<ListItem> = app.tags.iter().map(|t| ListItem::new(t.as_str())).collect();
+    let tags_list = List::new(tags)
+        .block(create_block(
+            "Tags (n - new, d - delete)",
+            app.manage_focus == ManageFocus::Tags,
+        ))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(tags_list, rects[1], &mut app.manage_tags_list.state);
+
+    if let Some(ref prompt) = app.manage_prompt {
+        let title = match prompt {
+            ManagePrompt::NewCategory => "New category (Enter - create, Esc - discard)".to_owned(),
+            ManagePrompt::EditCategory(name) => {
+                format!("Save path for {name} (Enter - apply, Esc - discard)")
+            }
+            ManagePrompt::NewTags => {
+                "New tags, comma separated (Enter - create, Esc - discard)".to_owned()
+            }
+        };
+
+        let width = std::cmp::min(size.width, 80);
+        let rect = Rect::new((size.width - width) / 2, size.height / 3, width, 3);
+
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let paragraph = Paragraph::new(text_input_line(&app.manage_input, true)).block(block);
+
+        f.render_widget(Clear, rect);
+        f.render_widget(paragraph, rect);
+    }
+}
+
+fn draw_notification<B: Backend>(f: &mut Frame<B>, title: &str, text: &str) {
+    let size = f.size();
+    let area = create_centered_rect(70, 40, size);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text = vec![
+        Line::from(Span::styled(
+            title,
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::raw("")),
+        Line::from(Span::raw(text)),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .style(Style::default())
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_dialog<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let width = std::cmp::min(size.width - 2, 60);
+    let height = 14;
+
+    let left = (size.width - width) / 2;
+    let top = size.height / 4;
+
+    let rect = Rect::new(left, top, width, height);
+
+    f.render_widget(Clear, rect);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    f.render_widget(block, rect);
+
+    let vchunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints([Constraint::Min(9), Constraint::Length(3)].as_ref())
+        .split(rect);
+
+    let action = app.current_action.as_ref().unwrap();
+    let (question, subject) = match action {
+        Action::Delete => (
+            "Are you sure you want to delete the torrent?",
+            app.get_selected_torrent().unwrap().name.clone(),
+        ),
+        Action::DeleteFiles => (
+            "Are you sure you want to delete the torrent AND FILES?",
+            app.get_selected_torrent().unwrap().name.clone(),
+        ),
+        Action::RemoveCategory(category) => (
+            "Are you sure you want to remove the category?",
+            category.clone(),
+        ),
+        Action::DeleteTag(tag) => ("Are you sure you want to delete the tag?", tag.clone()),
+        Action::CopyNames(names) => (
+            "Copy the names of all torrents in view to the clipboard?",
+            format!("{} names", names.len()),
+        ),
+        Action::ForceStartAll(hashes) => (
+            "Force start all torrents in view, ignoring the queue limits?",
+            format!("{} torrents", hashes.len()),
+        ),
+        Action::AddTrackersToAll(hashes, urls) => (
+            "Add the trackers to all torrents in view?",
+            format!("{} trackers, {} torrents", urls.len(), hashes.len()),
+        ),
+        Action::JumpToDuplicate(hash) => (
+            "The torrent is already added. Jump to it?",
+            app.torrents
+                .iter()
+                .find(|t| &t.hash == hash)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| hash.clone()),
+        ),
+        Action::PauseCategory(category) => (
+            "Are you sure you want to pause all torrents in the category?",
+            format!(
+                "{} ({} torrents)",
+                app.category_name(category),
+                app.get_category_torrents(category).len()
+            ),
+        ),
+    };
+    let is_delete = matches!(action, Action::Delete | Action::DeleteFiles);
+    let mut text = vec![
+        Line::from(Span::raw(question)),
+        Line::from(Span::raw("")),
+        Line::from(Span::styled(
+            subject,
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    // the view will go empty after the delete
+    if is_delete
+        && app.selected_category != SelectedCategory::All
+        && app.get_visible_torrents().len() == 1
+    {
+        text.push(Line::from(Span::raw("")));
+        text.push(Line::from(Span::raw(format!(
+            "This is the last torrent in {}",
+            app.selected_category_name()
+        ))));
+    }
+
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    f.render_widget(paragraph, vchunks[0]);
+
+    let hchunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(3)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+        .split(vchunks[1]);
+
+    let (style1, style2) = match app.confirm {
+        true => (
+            Style::default().add_modifier(Modifier::REVERSED),
+            Style::default(),
+        ),
+        false => (
+            Style::default(),
+            Style::default().add_modifier(Modifier::REVERSED),
+        ),
+    };
+
+    let ok_paragraph = Paragraph::new("Ok")
+        .style(style1)
+        .alignment(Alignment::Center);
+
+    f.render_widget(ok_paragraph, hchunks[0]);
+
+    let cancel_paragraph = Paragraph::new("Cancel")
+        .style(style2)
+        .alignment(Alignment::Center);
+
+    f.render_widget(cancel_paragraph, hchunks[1]);
+}
+
+fn draw_info<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .title("Info")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let torrent = app.get_current_torrent().unwrap();
+    let mut text = torrent.to_info_page(app.config.speed_in_bits);
+    match app.pending_rechecks.get(&torrent.hash) {
+        Some(RecheckStep::Requested(_)) => {
+            text.push_str("\nPending: recheck requested, then resume")
+        }
+        Some(RecheckStep::Checking) => text.push_str("\nPending: checking, then resume"),
+        None => {}
+    }
+    if let Some(ref properties) = app.current_torrent_properties {
+        text.push('\n');
+        text.push_str(&properties.to_connections_line());
+    }
+    if let Some(ref trackers) = app.current_torrent_trackers {
+        text.push('\n');
+        text.push_str(&trackers_info_page(trackers));
+    }
+    let lines: Vec<String> = if app.info_state.is_wrapped {
+        // the scroll indicator takes the last column
+        wrap_text(&text, size.width.saturating_sub(3).into())
+    } else {
+        text.lines().map(ToOwned::to_owned).collect()
+    };
+    app.info_state
+        .resize(lines.len(), size.height.saturating_sub(2));
+
+    let query = app.info_state.search.value().to_lowercase();
+    let matches = if query.is_empty() || app.info_state.is_searching {
+        Vec::new()
+    } else {
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect()
+    };
+    app.info_state.set_search_matches(matches);
+
+    let state = &app.info_state;
+    let block = if state.is_searching {
+        let mut spans = vec![Span::raw(" /")];
+        spans.extend(text_input_line(&state.search, true).spans);
+        spans.push(Span::raw(" (Enter - find, Esc - discard) "));
+        block.title(
+            Title::from(Line::from(spans))
+                .position(Position::Bottom)
+                .alignment(Alignment::Left),
+        )
+    } else if !state.search.is_empty() {
+        let position = match state.current_match {
+            Some(i) => format!("{}/{}", i + 1, state.search_matches.len()),
+            None => format!("{} matches", state.search_matches.len()),
+        };
+        block.title(
+            Title::from(format!(
+                " /{} ({position}, n/N - next/prev, Esc - clear) ",
+                state.search.value()
+            ))
+            .position(Position::Bottom)
+            .alignment(Alignment::Left),
+        )
+    } else {
+        block
+    };
+
+    let highlight = if state.is_searching {
+        ""
+    } else {
+        query.as_str()
+    };
+    let text: Vec<Line> = lines
         .iter()
-        .map(|f| f.name.clone())
+        .map(|line| highlight_search_match(line, highlight, highlight))
+        .collect();
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Left)
+        .scroll((state.scroll, 0));
+
+    f.render_widget(paragraph, size);
+    draw_scroll_indicator(f, size, &app.info_state);
+}
+
+/// Word wrap, a word wider than the view is split
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = std::cmp::max(width, 1);
+    let text_width = |s: &str| Span::raw(s.trim_end()).width();
+
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        for word in line.split_inclusive(' ') {
+            if !current.is_empty() && text_width(&current) + text_width(word) > width {
+                lines.push(current.trim_end().to_owned());
+                current.clear();
+            }
+            current.push_str(word);
+            while text_width(&current) > width {
+                let i = split_index(&current, width);
+                lines.push(current[..i].to_owned());
+                current.drain(..i);
+            }
+        }
+        lines.push(current.trim_end().to_owned());
+    }
+
+    lines
+}
+
+// byte index of the first char past the width, at least one char
+fn split_index(s: &str, width: usize) -> usize {
+    let mut w = 0;
+    for (i, c) in s.char_indices() {
+        w += Span::raw(&s[i..i + c.len_utf8()]).width();
+        if w > width {
+            return std::cmp::max(i, c.len_utf8());
+        }
+    }
+    s.len()
+}
+
+fn draw_preferences<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .title("Preferences")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let text = app.preferences.to_page();
+    app.preferences_state
+        .resize(text.lines().count(), size.height.saturating_sub(2));
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Left)
+        .scroll((app.preferences_state.scroll, 0));
+
+    f.render_widget(paragraph, size);
+    draw_scroll_indicator(f, size, &app.preferences_state);
+}
+
+fn draw_ratio<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .title("Global ratio")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    // all torrents, filters do not apply
+    let text = RatioSummary::from_torrents(app.torrents.iter()).to_page();
+    let paragraph = Paragraph::new(text).block(block).alignment(Alignment::Left);
+
+    f.render_widget(paragraph, size);
+}
+
+fn draw_completed<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .title("Completed this session (Enter - go to torrent, Esc - close)")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let now = unix_now();
+    app.completed_list.items = app
+        .recently_completed
+        .iter()
+        .map(|completed| {
+            let ago = humanize_duration((now - completed.completed_at).max(0));
+            format!("{ago:>8} ago  {}", completed.name)
+        })
+        .collect();
+
+    let items: Vec<ListItem> = if app.completed_list.items.is_empty() {
+        vec![ListItem::new("Nothing has completed yet")]
+    } else {
+        app.completed_list
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.as_str()))
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(block)
+        .start_corner(Corner::TopLeft)
+        .style(Style::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, size, &mut app.completed_list.state);
+}
+
+fn draw_errored<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let mut size = f.size();
+    if app.is_editing_errored_location {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+            .split(size);
+        size = chunks[0];
+
+        let block = Block::default()
+            .title("New location, the data is moved (Enter - apply, Esc - cancel)")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let paragraph =
+            Paragraph::new(text_input_line(&app.errored_location_input, true)).block(block);
+        f.render_widget(paragraph, chunks[1]);
+    }
+
+    let block = Block::default()
+        .title("Errored (r - recheck & resume, s - resume, l - set location, Enter - go to torrent, Esc - close)")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    // the reason is the state, plus whether the data is there for local paths
+    let items: Vec<String> = app
+        .get_errored_torrents()
+        .iter()
+        .map(|t| {
+            let reason = t.state.error_title().unwrap_or_default();
+            let location = if app.errored_not_found.contains(&t.hash) {
+                format!("{} (not found locally)", t.save_path)
+            } else {
+                t.save_path.clone()
+            };
+            format!("{}\n    {reason}, {location}", t.name)
+        })
+        .collect();
+    app.errored_list.items = items;
+    // recovered torrents leave the list under the selection
+    let len = app.errored_list.items.len();
+    let selected = match app.errored_list.state.selected() {
+        _ if len == 0 => None,
+        Some(i) if i < len => Some(i),
+        _ => Some(0),
+    };
+    app.errored_list.state.select(selected);
+
+    let items: Vec<ListItem> = if app.errored_list.items.is_empty() {
+        vec![ListItem::new("No errored torrents")]
+    } else {
+        app.errored_list
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.as_str()))
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(block)
+        .start_corner(Corner::TopLeft)
+        .style(Style::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, size, &mut app.errored_list.state);
+}
+
+fn draw_files<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+    app.files_list_rect = Some(size);
+
+    let block = Block::default()
+        .title("Files (Enter - open, Space - download/skip, +/- - priority)")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    app.files_list.items = file_rows(app.current_torrent_files.as_ref().unwrap())
+        .iter()
+        .map(FileRow::to_line)
         .collect();
 
     let items: Vec<ListItem> = app
@@ -515,6 +1566,127 @@ fn draw_files<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     f.render_stateful_widget(list, size, &mut app.files_list.state);
 }
 
+fn draw_inbox<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let block = Block::default()
+        .title(format!(
+            "Add from inbox{} (Enter - add, p - paused, Esc - close)",
+            if app.add_paused { ", paused" } else { "" }
+        ))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    app.inbox_list.items = app
+        .inbox_files
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let items: Vec<ListItem> = app
+        .inbox_list
+        .items
+        .iter()
+        .map(|f| ListItem::new(f.as_str()))
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .start_corner(Corner::TopLeft)
+        .style(Style::default())
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, size, &mut app.inbox_list.state);
+}
+
+fn draw_peers<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let size = f.size();
+
+    let mut peers: Vec<_> = app.current_torrent_peers.as_ref().unwrap().iter().collect();
+    // stable order between syncs
+    peers.sort_by(|(a_key, a), (b_key, b)| {
+        b.dl_speed
+            .cmp(&a.dl_speed)
+            .then(b.up_speed.cmp(&a.up_speed))
+            .then(a_key.cmp(b_key))
+    });
+    let speed_in_bits = app.config.speed_in_bits;
+    app.peers_table.items = peers
+        .into_iter()
+        .map(|(_, peer)| peer.to_row(speed_in_bits))
+        .collect();
+
+    if let Some(i) = app.peers_table.state.selected() {
+        if i >= app.peers_table.items.len() {
+            app.peers_table
+                .state
+                .select(app.peers_table.items.len().checked_sub(1));
+        }
+    }
+
+    let headers = [
+        "Address",
+        "Country",
+        "Connection",
+        "Flags",
+        "Client",
+        "%",
+        "Down",
+        "Up",
+        "Downloaded",
+        "Uploaded",
+    ];
+    let head_row = Row::new(headers.into_iter().map(Cell::from))
+        .height(1)
+        .bottom_margin(1);
+
+    let rows: Vec<Row> = app
+        .peers_table
+        .items
+        .iter()
+        .map(|item| Row::new(item.iter().map(|c| Cell::from(c.as_str()))))
+        .collect();
+
+    let table_constraints = [
+        Constraint::Percentage(18), // address
+        Constraint::Percentage(6),  // country
+        Constraint::Percentage(8),  // connection
+        Constraint::Percentage(6),  // flags
+        Constraint::Percentage(18), // client
+        Constraint::Percentage(6),  // progress
+        Constraint::Percentage(9),  // down
+        Constraint::Percentage(9),  // up
+        Constraint::Percentage(10), // downloaded
+        Constraint::Percentage(10), // uploaded
+    ];
+
+    let title = format!("Peers: {}", app.current_torrent.as_ref().unwrap().name);
+    let table = Table::new(rows)
+        .header(head_row)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(title)
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+        .widths(&table_constraints);
+
+    f.render_stateful_widget(table, size, &mut app.peers_table.state);
+}
+
+// keys within the window are handled before the next redraw
+const KEY_BURST_WINDOW: Duration = Duration::from_millis(5);
+const KEY_BURST_MAX: Duration = Duration::from_millis(50);
+
 pub async fn run<B: Backend>(
     terminal: &mut Terminal<B>,
     app: Arc<Mutex<App>>,
@@ -530,21 +1702,78 @@ pub async fn run<B: Backend>(
     let tick_rate = Duration::from_millis(1000);
     let mut last_tick = Instant::now();
     let mut redraw = true;
+    let mut key_burst_start: Option<Instant> = None;
 
     loop {
-        if redraw {
+        // key repeat is drawn once the burst is over, but not less often than KEY_BURST_MAX
+        let is_in_key_burst = key_burst_start
+            .map(|ts| ts.elapsed() < KEY_BURST_MAX)
+            .unwrap_or(false);
+        if redraw && !is_in_key_burst {
+            key_burst_start = None;
+            redraw = false;
             let mut app = app.lock().await;
+            if app.should_clear_screen {
+                app.should_clear_screen = false;
+                let _ = terminal.clear();
+            }
             let _ = terminal.draw(|f| {
                 match app.current_route {
-                    Route::Torrents | Route::Search | Route::Dialog => draw_torrents(f, &mut app),
+                    Route::Torrents | Route::Search => draw_torrents(f, &mut app),
+                    Route::Dialog => match app.current_action {
+                        Some(Action::PauseCategory(_)) => draw_categories(f, &mut app),
+                        Some(Action::RemoveCategory(_)) | Some(Action::DeleteTag(_)) => {
+                            draw_manage(f, &mut app)
+                        }
+                        _ => draw_torrents(f, &mut app),
+                    },
                     Route::Sort => {
                         draw_torrents(f, &mut app);
                         draw_sort(f, &mut app);
                     }
+                    Route::Commands => {
+                        draw_torrents(f, &mut app);
+                        draw_commands(f, &mut app);
+                    }
+                    Route::Tags => {
+                        draw_torrents(f, &mut app);
+                        draw_tags(f, &mut app);
+                    }
+                    Route::GoTo => {
+                        draw_torrents(f, &mut app);
+                        draw_goto(f, &mut app);
+                    }
+                    Route::SetCategory => {
+                        draw_torrents(f, &mut app);
+                        draw_set_category(f, &mut app);
+                    }
+                    Route::NewCategory => {
+                        draw_torrents(f, &mut app);
+                        draw_new_category(f, &mut app);
+                    }
+                    Route::Manage => draw_manage(f, &mut app),
                     Route::Help => draw_help(f, &mut app),
                     Route::Categories => draw_categories(f, &mut app),
                     Route::Info => draw_info(f, &mut app),
+                    Route::Preferences => draw_preferences(f, &mut app),
+                    Route::Ratio => draw_ratio(f, &app),
+                    Route::Completed => draw_completed(f, &mut app),
+                    Route::Errored => draw_errored(f, &mut app),
+                    Route::AddTorrent => {
+                        draw_torrents(f, &mut app);
+                        draw_add_torrent(f, &mut app);
+                    }
+                    Route::Login => {
+                        draw_torrents(f, &mut app);
+                        draw_login(f, &mut app);
+                    }
+                    Route::AddTrackers => {
+                        draw_torrents(f, &mut app);
+                        draw_add_trackers(f, &mut app);
+                    }
                     Route::Files => draw_files(f, &mut app),
+                    Route::Inbox => draw_inbox(f, &mut app),
+                    Route::Peers => draw_peers(f, &mut app),
                 }
 
                 if app.is_connected && app.current_action.is_some() {
@@ -558,21 +1787,112 @@ pub async fn run<B: Backend>(
                             "File not found",
                             "File not found or remote server",
                         ),
+                        Notification::TorrentNotFound => draw_notification(
+                            f,
+                            "Torrent not found",
+                            "Torrent no longer exists",
+                        ),
+                        Notification::UnknownCategory(name) => draw_notification(
+                            f,
+                            "Category not found",
+                            &format!("Category \"{name}\" does not exist, showing all torrents"),
+                        ),
+                        Notification::CommandFailed(reason) => {
+                            draw_notification(f, "Command failed", reason)
+                        }
+                        Notification::NoErroredTorrents => draw_notification(
+                            f,
+                            "No errors",
+                            "No errored torrents in the current view",
+                        ),
+                        Notification::ResumedTorrents(0) => draw_notification(
+                            f,
+                            "Resume",
+                            "No paused torrents in the current view",
+                        ),
+                        Notification::InvalidTrackerUrl(url) => draw_notification(
+                            f,
+                            "Add trackers",
+                            &format!("Invalid tracker URL: {url}"),
+                        ),
+                        Notification::Reannounced(n) => draw_notification(
+                            f,
+                            "Reannounce",
+                            &format!("Reannounced {n} torrent(s) to all their trackers"),
+                        ),
+                        Notification::AddedTrackers(n) => draw_notification(
+                            f,
+                            "Add trackers",
+                            &format!("Updated {n} torrent(s)"),
+                        ),
+                        Notification::UploadLimitSet(limit) => draw_notification(
+                            f,
+                            "Upload limit",
+                            &format!("Upload limit: {}", humanize_up_limit(*limit, app.config.speed_in_bits)),
+                        ),
+                        Notification::CopiedNames(n) => draw_notification(
+                            f,
+                            "Copy",
+                            &format!("Copied {n} name(s) to the clipboard"),
+                        ),
+                        Notification::EmptyInbox => draw_notification(
+                            f,
+                            "Inbox",
+                            "No .torrent files in the inbox directory",
+                        ),
+                        Notification::InboxFailed(reason) => {
+                            draw_notification(f, "Inbox", reason)
+                        }
+                        Notification::ReadTorrentFileFailed(reason) => {
+                            draw_notification(f, "Add torrent", reason)
+                        }
+                        Notification::CategoryNotCreated(name) => draw_notification(
+                            f,
+                            "New category",
+                            &format!("Could not create \"{name}\", it already exists or the name is not valid"),
+                        ),
+                        Notification::AddTorrentFailed => draw_notification(
+                            f,
+                            "Add torrent",
+                            "qBittorrent could not add the torrent",
+                        ),
+                        Notification::ServerRestarted => draw_notification(
+                            f,
+                            "Server restarted",
+                            "qBittorrent was restarted, everything is resynced",
+                        ),
+                        Notification::ForceStartedTorrents(n) => draw_notification(
+                            f,
+                            "Force start",
+                            &format!("Force started {n} torrent(s)"),
+                        ),
+                        Notification::ResumedTorrents(n) => {
+                            draw_notification(f, "Resume", &format!("Resumed {n} torrent(s)"))
+                        }
                     }
                 }
 
-                if !app.is_connected {
-                    let text = format!(
-                        "Connection error! Trying to reconnect... {}",
-                        app.error_reconnection_attempt_n
-                    );
+                if !app.is_connected && app.current_route != Route::Login {
+                    let text = if app.is_reconnecting {
+                        "Reconnecting...".to_owned()
+                    } else if let Some(ref reason) = app.login_error {
+                        format!("{reason} (L - log in, q - quit)")
+                    } else {
+                        let secs = app
+                            .next_reconnection_ts
+                            .saturating_duration_since(Instant::now())
+                            .as_secs();
+                        let reason = app.connection_error.as_deref().unwrap_or("Connection error!");
+                        format!(
+                            "{reason} Trying to reconnect in {secs}s... {} (Enter - reconnect now)",
+                            app.error_reconnection_attempt_n
+                        )
+                    };
                     draw_notification(f, "Connection error", &text);
                 }
             });
         }
 
-        redraw = false;
-
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
@@ -583,7 +1903,11 @@ pub async fn run<B: Backend>(
                     Some(Ok(e)) => match e {
                         Event::Key(e) => {
                             let mut app = app.lock().await;
-                            if app.is_connected {
+                            // the data on screen is stale, so the key only wakes it up
+                            let was_idle = app.wake_up().await;
+                            if was_idle {
+                                tracing::debug!("Woken up");
+                            } else if app.is_connected {
                                 if app.notification.is_some() {
                                     app.handle_notification_key_event(e).await;
                                 } else {
@@ -593,14 +1917,32 @@ pub async fn run<B: Backend>(
                                 app.handle_disconnected_key_event(e).await;
                             }
                             redraw = true;
+                            key_burst_start.get_or_insert_with(Instant::now);
                         }
                         Event::Mouse(e) => {
                             let mut app = app.lock().await;
-                            if app.is_connected && app.notification.is_none() {
+                            // moves are not input, the pointer may only pass over the terminal
+                            if e.kind == MouseEventKind::Moved {
+                                continue;
+                            }
+                            if !app.wake_up().await
+                                && app.is_connected
+                                && app.notification.is_none()
+                            {
                                 app.handle_mouse_event(e).await;
                             }
                             redraw = true;
                         }
+                        Event::Paste(text) => {
+                            let mut app = app.lock().await;
+                            if !app.wake_up().await
+                                && app.is_connected
+                                && app.notification.is_none()
+                            {
+                                app.handle_paste_event(text).await;
+                            }
+                            redraw = true;
+                        }
                         Event::Resize(_, _) => {
                             redraw = true;
                         }
@@ -617,9 +1959,12 @@ pub async fn run<B: Backend>(
                     }
                 }
             }
+            _ = sleep(KEY_BURST_WINDOW), if key_burst_start.is_some() => {
+                key_burst_start = None;
+            }
             _ = sleep(timeout) => {
                 let mut app = app.lock().await;
-                app.sync().await;
+                app.sync_or_wait_for_reconnection().await;
                 app.trace_send_sync_event_n += 1;
                 redraw = true;
             }
@@ -655,3 +2000,116 @@ pub async fn run<B: Backend>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::{config::Config, model::tests::torrent};
+
+    fn test_app() -> App {
+        let (api_tx, _) = mpsc::channel(1);
+        let mut app = App::new("http://localhost:8080", api_tx, Config::default());
+
+        let mut ubuntu = torrent("a1", "ubuntu-22.04.iso");
+        ubuntu.category = "Linux".to_owned();
+        ubuntu.size = 3 * 1024 * 1024 * 1024 / 2;
+        ubuntu.total_size = ubuntu.size;
+        ubuntu.progress = 0.5;
+        ubuntu.dlspeed = 1024 * 1024;
+        ubuntu.eta = 90 * 60;
+        ubuntu.state = TorrentInfoState::Downloading;
+
+        let mut debian = torrent("b2", "debian-12.iso");
+        debian.size = 600 * 1024 * 1024;
+        debian.total_size = debian.size;
+        debian.progress = 1.0;
+        debian.upspeed = 2048;
+        debian.state = TorrentInfoState::Uploading;
+
+        app.torrents = vec![ubuntu, debian];
+        app
+    }
+
+    // one line per terminal row, wide chars leave an empty cell behind
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(160, 20)).unwrap();
+        terminal.draw(|f| draw_torrents(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.symbol.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn torrents_table_has_headers_names_and_humanized_values() {
+        let mut app = test_app();
+        let screen = render(&mut app);
+
+        for header in ["Category", "Name", "Size", "%", "Down", "Up", "Eta"] {
+            assert!(screen.contains(header), "no {header} header in\n{screen}");
+        }
+        assert!(screen.contains("ubuntu-22.04.iso"));
+        assert!(screen.contains("debian-12.iso"));
+        assert!(screen.contains("Linux"));
+        assert!(screen.contains("1.5 G"));
+        assert!(screen.contains("600 M"));
+        assert!(screen.contains("50.0%"));
+        assert!(screen.contains("100.0%"));
+        assert!(screen.contains("1 M/s"));
+        assert!(screen.contains("2 K/s"));
+        assert!(screen.contains("1h 30m"));
+    }
+
+    #[test]
+    fn search_block_takes_space_from_the_table() {
+        let mut app = test_app();
+        render(&mut app);
+        let full_height = app.torrents_table_rect.unwrap().height;
+
+        app.search_input.set_value("ubuntu");
+        let screen = render(&mut app);
+        let height = app.torrents_table_rect.unwrap().height;
+
+        assert!(height < full_height);
+        assert!(screen.contains("ubuntu-22.04.iso"));
+        assert!(!screen.contains("debian-12.iso"));
+    }
+
+    #[test]
+    fn search_block_shows_the_prompt_while_typing() {
+        let mut app = test_app();
+        assert!(!render(&mut app).contains("Search (Enter - apply"));
+
+        app.current_route = Route::Search;
+        app.search_input.set_value("deb");
+        let screen = render(&mut app);
+        assert!(screen.contains("Search (Enter - apply, Esc - discard)"));
+        assert!(screen.contains("debian-12.iso"));
+        assert!(!screen.contains("ubuntu-22.04.iso"));
+    }
+
+    #[test]
+    fn search_highlights_every_match() {
+        let line = highlight_search_match("Öl and öl, ÖL", "öl", "öl");
+        let highlighted: Vec<&str> = line
+            .spans
+            .iter()
+            .filter(|span| span.style.add_modifier.contains(Modifier::UNDERLINED))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(highlighted, ["Öl", "öl", "ÖL"]);
+
+        let line = highlight_search_match("Öl", "", "");
+        assert_eq!(line.spans.len(), 1);
+    }
+}